@@ -1,7 +1,14 @@
+use crate::cache::ScanCache;
+use crate::changes::ChangeReport;
 use crate::cli::CheckArgs;
-use crate::openapi::{extract_endpoints, Endpoint};
-use crate::scanner::{ContentScanner, FileScanner};
+use crate::detector::DetectorRegistry;
+use crate::openapi::{extract_endpoints, Endpoint, OpenApiSpec, SpecDialect};
+use crate::scanner::{ContentScanner, FileScanner, MatchLocation, UnknownCall};
+use crate::sourcemap::BundleMode;
+use crate::template::ambiguous_paths;
+use std::collections::BTreeMap;
 use std::path::Path;
+use tokio::task;
 
 /// Analysis result for an endpoint
 #[derive(Debug, Clone)]
@@ -10,6 +17,20 @@ pub struct EndpointResult {
     pub status: EndpointStatus,
     pub usage_count: usize,
     pub files: Vec<String>,
+    /// Files where usage was only detected through the operationId method name
+    pub operation_id_files: Vec<String>,
+    pub tags: Vec<String>,
+    /// Whether the operation is marked `deprecated: true` in the spec
+    pub deprecated: bool,
+    /// Every individual hit, ordered by file and position
+    pub locations: Vec<MatchLocation>,
+}
+
+impl EndpointResult {
+    /// Deprecated operations that code still calls need to be migrated
+    pub fn is_deprecated_in_use(&self) -> bool {
+        self.deprecated && self.status == EndpointStatus::Used
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,41 +45,87 @@ pub struct AnalysisResult {
     pub endpoints: Vec<EndpointResult>,
     pub total_files_scanned: usize,
     pub scan_time_ms: u128,
+    pub dialect: SpecDialect,
+    pub webhook_count: usize,
+    /// With `--unknown`: calls whose path matches no spec endpoint, except
+    /// those to allowed hosts
+    pub unknown_calls: Vec<UnknownCall>,
+}
+
+impl AnalysisResult {
+    /// Deprecated endpoints that still have usages
+    pub fn deprecated_in_use(&self) -> Vec<&EndpointResult> {
+        self.endpoints.iter().filter(|r| r.is_deprecated_in_use()).collect()
+    }
 }
 
 /// Main endpoint analyzer
 pub struct EndpointAnalyzer {
+    spec: OpenApiSpec,
     spec_endpoints: Vec<Endpoint>,
+    base_paths: Vec<String>,
     cli: CheckArgs,
+    detectors: DetectorRegistry,
 }
 
 impl EndpointAnalyzer {
-    pub fn new(spec: crate::openapi::OpenApiSpec, cli: CheckArgs) -> Self {
+    pub fn new(spec: OpenApiSpec, cli: CheckArgs) -> Self {
         let spec_endpoints = extract_endpoints(&spec);
-        Self { spec_endpoints, cli }
+        let base_paths = spec.base_paths();
+        Self {
+            spec,
+            spec_endpoints,
+            base_paths,
+            cli,
+            detectors: DetectorRegistry::default(),
+        }
+    }
+
+    /// Use a custom set of language usage detectors instead of the built-ins
+    pub fn with_detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.detectors = detectors;
+        self
     }
 
     /// Analyze a directory for endpoint usage
     pub async fn analyze_directory(&self, dir: &Path) -> anyhow::Result<AnalysisResult> {
         let start_time = std::time::Instant::now();
+        self.warn_ambiguous_paths();
+        let content_scanner = self.content_scanner()?;
 
-        // Find all source files
-        let scanner = FileScanner::new(self.cli.clone());
-        let files = scanner.find_files(dir)?;
-
-        // Create content scanner
-        let content_scanner = ContentScanner::new(&self.spec_endpoints)?;
-
-        // Scan files for endpoint usage
-        let usage_results = content_scanner.scan_files(files.clone()).await?;
+        // Scan files for endpoint usage on a blocking worker pool
+        let scanner = FileScanner::new(self.cli.clone()).with_detectors(&self.detectors);
+        let scan_dir = dir.to_path_buf();
+        let cache = (!self.cli.no_cache).then(|| ScanCache::open(dir, content_scanner.fingerprint()));
+        let scan = task::spawn_blocking(move || {
+            // A read-only checkout still gets a full (uncached) result
+            let mut writer = cache.as_ref().and_then(|cache| {
+                cache.writer()
+                    .map_err(|e| eprintln!("Warning: could not write scan cache: {:#}", e))
+                    .ok()
+            });
+            let scan = content_scanner.scan_directory(&scanner, &scan_dir, cache.as_ref(), writer.as_mut())?;
+            if let Some(Err(e)) = writer.map(|writer| writer.finish()) {
+                eprintln!("Warning: could not write scan cache: {:#}", e);
+            }
+            anyhow::Ok(scan)
+        }).await??;
+        let usage_results = scan.usage;
+        let unknown_calls = if self.cli.unknown {
+            scan.unknown.into_iter()
+                .filter(|call| !call.host.as_deref().is_some_and(|host| is_allowed_host(host, &self.cli.allow_host)))
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         // Build results
         let mut results = Vec::new();
         for endpoint in &self.spec_endpoints {
-            let (_total_matches, files) = usage_results.get(endpoint)
-                .map(|(count, files)| (*count, files.clone()))
-                .unwrap_or((0, Vec::new()));
+            let usage = usage_results.get(endpoint).cloned().unwrap_or_default();
+            let files = usage.files;
 
+            let operation = self.spec.operation(endpoint);
             let file_count = files.len();
             let status = if file_count > 0 {
                 EndpointStatus::Used
@@ -71,6 +138,10 @@ impl EndpointAnalyzer {
                 status,
                 usage_count: file_count, // Number of files, not total matches
                 files,
+                operation_id_files: usage.operation_id_files,
+                tags: operation.map(|op| op.tags.clone()).unwrap_or_default(),
+                deprecated: operation.is_some_and(|op| op.deprecated),
+                locations: usage.locations,
             };
 
             results.push(result);
@@ -87,6 +158,10 @@ impl EndpointAnalyzer {
             filtered_results.retain(|r| regex.is_match(&r.endpoint.to_string()));
         }
 
+        if !self.cli.tag.is_empty() {
+            filtered_results.retain(|r| r.tags.iter().any(|tag| self.cli.tag.contains(tag)));
+        }
+
         // Sort results by endpoint path, then by method
         filtered_results.sort_by(|a, b| {
             match a.endpoint.path.cmp(&b.endpoint.path) {
@@ -99,10 +174,94 @@ impl EndpointAnalyzer {
 
         Ok(AnalysisResult {
             endpoints: filtered_results,
-            total_files_scanned: files.len(),
+            total_files_scanned: scan.files_scanned,
             scan_time_ms: scan_time,
+            dialect: self.spec.dialect,
+            webhook_count: self.spec.webhooks.len(),
+            unknown_calls,
         })
     }
 
+    /// Endpoints whose usage changed between the git revision `rev` and the
+    /// working tree, scanning only the changed files
+    pub async fn analyze_changes(&self, dir: &Path, rev: &str) -> anyhow::Result<ChangeReport> {
+        let content_scanner = self.content_scanner()?;
+        let scanner = FileScanner::new(self.cli.clone()).with_detectors(&self.detectors);
+        let mut report = crate::changes::compare_with_revision(&content_scanner, &scanner, dir, rev).await?;
+
+        if let Some(pattern) = &self.cli.pattern {
+            let regex = regex::Regex::new(pattern)?;
+            report.endpoints.retain(|c| regex.is_match(&c.endpoint.to_string()));
+        }
+
+        if !self.cli.tag.is_empty() {
+            report.endpoints.retain(|c| {
+                self.spec.operation(&c.endpoint)
+                    .is_some_and(|op| op.tags.iter().any(|tag| self.cli.tag.contains(tag)))
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Calls matching endpoints that are equally specific count for each of them
+    fn warn_ambiguous_paths(&self) {
+        let mut by_method: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for endpoint in &self.spec_endpoints {
+            by_method.entry(endpoint.method.as_str()).or_default().push(&endpoint.path);
+        }
 
-}
\ No newline at end of file
+        for (method, paths) in by_method {
+            for ambiguity in ambiguous_paths(&paths) {
+                eprintln!("Warning: {} {} and {} {} are ambiguous; a call to {} counts for both",
+                          method, ambiguity.first, method, ambiguity.second, ambiguity.example);
+            }
+        }
+    }
+
+    fn content_scanner(&self) -> anyhow::Result<ContentScanner> {
+        let operation_ids: Vec<(Endpoint, String)> = self.spec.operations()
+            .filter_map(|(endpoint, operation)| operation.operation_id.clone().map(|id| (endpoint, id)))
+            .collect();
+        let scanner = ContentScanner::new(&self.spec_endpoints, &self.base_paths)?
+            .with_operation_ids(&operation_ids)
+            .with_detectors(self.detectors.clone())
+            .with_project_config(&self.cli.project)?;
+
+        if self.cli.bundles {
+            return Ok(scanner.with_bundle_mode(BundleMode { include_node_modules: self.cli.bundle_node_modules }));
+        }
+        Ok(scanner)
+    }
+}
+
+/// Whether `host` is on the allowlist; `*.example.com` covers every subdomain
+fn is_allowed_host(host: &str, allowlist: &[String]) -> bool {
+    allowlist.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        match allowed.strip_prefix("*.") {
+            Some(domain) => host.strip_suffix(domain).is_some_and(|subdomain| subdomain.ends_with('.')),
+            None => host == allowed,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_cover_subdomains_only() {
+        let allowlist = ["*.example.com".to_string(), "API.Stripe.com".to_string()];
+
+        assert!(is_allowed_host("a.example.com", &allowlist));
+        assert!(is_allowed_host("a.b.example.com", &allowlist));
+        assert!(!is_allowed_host("example.com", &allowlist));
+        assert!(!is_allowed_host("badexample.com", &allowlist));
+        assert!(!is_allowed_host("example.com.evil.io", &allowlist));
+
+        assert!(is_allowed_host("api.stripe.com", &allowlist));
+        assert!(!is_allowed_host("files.stripe.com", &allowlist));
+        assert!(!is_allowed_host("api.stripe.com", &[]));
+    }
+}
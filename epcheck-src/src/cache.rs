@@ -0,0 +1,283 @@
+use crate::openapi::Endpoint;
+use crate::scanner::{MatchLocation, UnknownCall};
+use crate::syntax::SourceFile;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory holding the scan cache, inside the scanned directory
+pub const CACHE_DIR_NAME: &str = ".epcheck-cache";
+
+const CACHE_FILE_NAME: &str = "scan.json";
+
+/// Size and modification time of a file; an unchanged stamp means an unchanged file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Seconds and nanoseconds since the Unix epoch, if the platform reports it
+    pub modified: Option<(u64, u32)>,
+}
+
+impl FileStamp {
+    pub fn of(metadata: &std::fs::Metadata) -> Self {
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| (since.as_secs(), since.subsec_nanos()));
+        Self { size: metadata.len(), modified }
+    }
+}
+
+/// What scanning one file produced. Files without results store only their
+/// stamp and hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub stamp: FileStamp,
+    /// Hash of the file content, for files touched without being changed
+    pub hash: u64,
+    /// Stamp of a bundle's sibling source map in `--bundles` mode, which its
+    /// remapped results depend on as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_stamp: Option<FileStamp>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<(Endpoint, Vec<MatchLocation>)>,
+    /// Calls of the file that need nothing from other files and address no endpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown: Vec<UnknownCall>,
+    /// Constants, imports and import-dependent calls of a parsed JS/TS file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<SourceFile>,
+}
+
+/// First line of the cache file; every further line is one [`CacheEntry`]
+#[derive(Serialize, Deserialize)]
+struct CacheHeader {
+    fingerprint: String,
+}
+
+/// Per-file scan results kept in `.epcheck-cache/` between runs.
+///
+/// Entries are only valid for the fingerprint they were written with, which
+/// covers the spec's endpoints and every pattern; a different spec or config
+/// starts from an empty cache.
+#[derive(Debug)]
+pub struct ScanCache {
+    dir: PathBuf,
+    fingerprint: String,
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl ScanCache {
+    /// Load the cache of the scanned directory `dir`. A missing, unreadable
+    /// or outdated cache gives an empty one.
+    pub fn open(dir: &Path, fingerprint: String) -> Self {
+        let dir = dir.join(CACHE_DIR_NAME);
+        let entries = read_entries(&dir.join(CACHE_FILE_NAME), &fingerprint).unwrap_or_default();
+        Self { dir, fingerprint, entries }
+    }
+
+    /// Entry for a file whose size and modification time are unchanged, as
+    /// are those of its source map
+    pub fn get(&self, path: &Path, stamp: FileStamp, map_stamp: Option<FileStamp>) -> Option<&CacheEntry> {
+        self.entries.get(path).filter(|entry| entry.stamp == stamp && entry.map_stamp == map_stamp)
+    }
+
+    /// Entry for a file whose content is unchanged, with an unchanged source map
+    pub fn get_by_hash(&self, path: &Path, hash: u64, map_stamp: Option<FileStamp>) -> Option<&CacheEntry> {
+        self.entries.get(path).filter(|entry| entry.hash == hash && entry.map_stamp == map_stamp)
+    }
+
+    /// Start writing the cache of a new scan. It replaces this one, dropping
+    /// deleted files, once [`CacheWriter::finish`] succeeds.
+    pub fn writer(&self) -> anyhow::Result<CacheWriter> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create cache directory '{}'", self.dir.display()))?;
+        // Keep the cache out of version control without touching the project's .gitignore
+        std::fs::write(self.dir.join(".gitignore"), "*\n")?;
+
+        // Write then rename, so an interrupted run never leaves a truncated cache
+        let temporary = self.dir.join(format!("{}.tmp", CACHE_FILE_NAME));
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        serde_json::to_writer(&mut writer, &CacheHeader { fingerprint: self.fingerprint.clone() })?;
+        writer.write_all(b"\n")?;
+
+        Ok(CacheWriter {
+            writer,
+            temporary,
+            target: self.dir.join(CACHE_FILE_NAME),
+            error: None,
+        })
+    }
+}
+
+fn read_entries(path: &Path, fingerprint: &str) -> Option<HashMap<PathBuf, CacheEntry>> {
+    let mut lines = BufReader::new(File::open(path).ok()?).lines();
+    let header: CacheHeader = serde_json::from_str(&lines.next()?.ok()?).ok()?;
+    if header.fingerprint != fingerprint {
+        return None;
+    }
+
+    lines
+        .map(|line| {
+            let entry: CacheEntry = serde_json::from_str(&line.ok()?).ok()?;
+            Some((entry.path.clone(), entry))
+        })
+        .collect()
+}
+
+/// Writes a scan's entries to disk as they arrive, so the cache never has to
+/// be held in memory as a whole
+pub struct CacheWriter {
+    writer: BufWriter<File>,
+    temporary: PathBuf,
+    target: PathBuf,
+    /// First write error; later entries are dropped and `finish` reports it
+    error: Option<anyhow::Error>,
+}
+
+impl CacheWriter {
+    pub fn write(&mut self, entry: &CacheEntry) {
+        if self.error.is_some() {
+            return;
+        }
+        let written = serde_json::to_writer(&mut self.writer, entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(self.writer.write_all(b"\n")?));
+        if let Err(e) = written {
+            self.error = Some(e);
+        }
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        if let Some(e) = self.error.take() {
+            let _ = std::fs::remove_file(&self.temporary);
+            return Err(e);
+        }
+        self.writer.flush()?;
+        std::fs::rename(&self.temporary, &self.target)?;
+        Ok(())
+    }
+}
+
+pub fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(content);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::HttpMethod;
+    use crate::scanner::MatchKind;
+
+    fn scan_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("epcheck-cache-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entry(path: &str, map_stamp: Option<FileStamp>) -> CacheEntry {
+        let location = MatchLocation {
+            file: path.to_string(),
+            line: 3,
+            column: 5,
+            snippet: "api.get('/users')".to_string(),
+            pattern: "call:literal".into(),
+            kind: MatchKind::Path,
+        };
+        CacheEntry {
+            path: PathBuf::from(path),
+            stamp: FileStamp { size: 42, modified: Some((1_700_000_000, 7)) },
+            hash: content_hash(b"api.get('/users')"),
+            map_stamp,
+            matches: vec![(Endpoint::new("/users".to_string(), HttpMethod::Get), vec![location])],
+            unknown: Vec::new(),
+            module: None,
+        }
+    }
+
+    fn write(dir: &Path, fingerprint: &str, entries: &[CacheEntry]) {
+        let mut writer = ScanCache::open(dir, fingerprint.to_string()).writer().unwrap();
+        for entry in entries {
+            writer.write(entry);
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let dir = scan_dir("round-trip");
+        let written = entry("src/api.ts", None);
+        write(&dir, "v1", std::slice::from_ref(&written));
+
+        let cache = ScanCache::open(&dir, "v1".to_string());
+        let path = Path::new("src/api.ts");
+        let read = cache.get(path, written.stamp, None).unwrap();
+        assert_eq!(read.hash, written.hash);
+        assert_eq!(read.matches, written.matches);
+
+        let touched = FileStamp { modified: None, ..written.stamp };
+        assert!(cache.get(path, touched, None).is_none());
+        assert!(cache.get_by_hash(path, written.hash, None).is_some());
+        assert!(cache.get_by_hash(path, written.hash + 1, None).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn source_map_changes_invalidate_bundles() {
+        let dir = scan_dir("map-stamp");
+        let map_stamp = FileStamp { size: 100, modified: Some((1_700_000_000, 0)) };
+        let written = entry("dist/app.js", Some(map_stamp));
+        write(&dir, "v1", std::slice::from_ref(&written));
+
+        let cache = ScanCache::open(&dir, "v1".to_string());
+        let path = Path::new("dist/app.js");
+        assert!(cache.get(path, written.stamp, Some(map_stamp)).is_some());
+        let rebuilt = FileStamp { size: 101, ..map_stamp };
+        assert!(cache.get(path, written.stamp, Some(rebuilt)).is_none());
+        assert!(cache.get_by_hash(path, written.hash, Some(rebuilt)).is_none());
+        assert!(cache.get(path, written.stamp, None).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn another_fingerprint_starts_empty() {
+        let dir = scan_dir("fingerprint");
+        let written = entry("src/api.ts", None);
+        write(&dir, "v1", std::slice::from_ref(&written));
+
+        let cache = ScanCache::open(&dir, "v2".to_string());
+        assert!(cache.get(Path::new("src/api.ts"), written.stamp, None).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_is_replaced_on_finish() {
+        let dir = scan_dir("rename");
+        write(&dir, "v1", &[entry("src/old.ts", None)]);
+        let cache_file = dir.join(CACHE_DIR_NAME).join(CACHE_FILE_NAME);
+        let temporary = dir.join(CACHE_DIR_NAME).join(format!("{}.tmp", CACHE_FILE_NAME));
+
+        let cache = ScanCache::open(&dir, "v1".to_string());
+        let mut writer = cache.writer().unwrap();
+        writer.write(&entry("src/new.ts", None));
+        // Until the scan finishes, the previous cache stays in place
+        assert!(temporary.is_file());
+        assert!(std::fs::read_to_string(&cache_file).unwrap().contains("src/old.ts"));
+
+        writer.finish().unwrap();
+        assert!(!temporary.exists());
+        let content = std::fs::read_to_string(&cache_file).unwrap();
+        assert!(content.contains("src/new.ts") && !content.contains("src/old.ts"));
+        assert_eq!(std::fs::read_to_string(dir.join(CACHE_DIR_NAME).join(".gitignore")).unwrap(), "*\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,567 @@
+use crate::cli::{CheckArgs, OutputFormat};
+use crate::openapi::Endpoint;
+use crate::constants::module_candidates;
+use crate::paths::normalize;
+use crate::scanner::{ContentScanner, FileMatches, FileScanner};
+use crate::report::{capitalize, grouped, markdown_list, print_csv_row, print_header, print_markdown_header};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsageChange {
+    /// Used now, unused at the base revision
+    NewlyUsed,
+    /// Used at the base revision, unused now
+    NewlyUnused,
+    /// Used before and after, by a different set of files
+    Changed,
+}
+
+impl UsageChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NewlyUsed => "newly used",
+            Self::NewlyUnused => "newly unused",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// How the usage of one endpoint changed since the base revision
+#[derive(Debug, Clone)]
+pub struct EndpointChange {
+    pub change: UsageChange,
+    pub endpoint: Endpoint,
+    /// Files that use the endpoint now but did not at the base revision
+    pub files_added: Vec<String>,
+    /// Files that used the endpoint at the base revision but no longer do
+    pub files_removed: Vec<String>,
+}
+
+/// Endpoint usage changes between a git revision and the working tree
+#[derive(Debug, Clone)]
+pub struct ChangeReport {
+    pub since: String,
+    /// Scanned files that differ from the base revision
+    pub changed_files: Vec<String>,
+    pub endpoints: Vec<EndpointChange>,
+}
+
+/// Which version of the scanned files to read
+#[derive(Clone, Copy)]
+enum Version<'a> {
+    /// The base revision; `prefix` is the scanned directory relative to the repository root
+    Base { rev: &'a str, prefix: &'a str },
+    Worktree,
+}
+
+/// A file that differs between the base revision and the working tree
+struct ChangedFile {
+    /// `/`-separated, relative to the scanned directory
+    path: String,
+    in_base: bool,
+    in_worktree: bool,
+}
+
+/// Compare endpoint usage at `rev` with the working tree of `dir`.
+///
+/// Only files changed since `rev` (including uncommitted and untracked ones)
+/// are scanned, in both versions, so the work grows with the diff rather than
+/// the repository. Constants imported from other files are read from the same
+/// version as the importing file. Endpoints that gained or lost their last
+/// usage in the diff are checked against the rest of the tree before being
+/// reported as newly used or unused.
+pub async fn compare_with_revision(
+    scanner: &ContentScanner,
+    files: &FileScanner,
+    dir: &Path,
+    rev: &str,
+) -> anyhow::Result<ChangeReport> {
+    let prefix = String::from_utf8(git(dir, &["rev-parse", "--show-prefix"]).await?)?.trim_end().to_string();
+    // A leading dash would make git read the revision as an option
+    if rev.starts_with('-')
+        || git(dir, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)]).await.is_err()
+    {
+        anyhow::bail!("Unknown git revision '{}'", rev);
+    }
+
+    let scannable = files.path_filter(dir)?;
+    let changed: Vec<ChangedFile> = changed_files(dir, rev).await?
+        .into_iter()
+        .filter(|file| scannable(Path::new(&file.path)))
+        .collect();
+
+    let base_paths: Vec<String> = changed.iter().filter(|file| file.in_base).map(|file| file.path.clone()).collect();
+    let worktree_paths: Vec<String> = changed.iter().filter(|file| file.in_worktree).map(|file| file.path.clone()).collect();
+    let base = Version::Base { rev, prefix: &prefix };
+    let before = usage_by_endpoint(scan_version(scanner, files, dir, base, &base_paths, &scannable).await?);
+    let after = usage_by_endpoint(scan_version(scanner, files, dir, Version::Worktree, &worktree_paths, &scannable).await?);
+
+    let empty = BTreeSet::new();
+    let touched: HashSet<&Endpoint> = before.keys().chain(after.keys()).collect();
+    let mut endpoints = Vec::new();
+    for endpoint in touched {
+        let old = before.get(endpoint).unwrap_or(&empty);
+        let new = after.get(endpoint).unwrap_or(&empty);
+        let files_added: Vec<String> = new.difference(old).cloned().collect();
+        let files_removed: Vec<String> = old.difference(new).cloned().collect();
+        if files_added.is_empty() && files_removed.is_empty() {
+            continue;
+        }
+
+        let change = if old.is_empty() {
+            UsageChange::NewlyUsed
+        } else if new.is_empty() {
+            UsageChange::NewlyUnused
+        } else {
+            UsageChange::Changed
+        };
+        endpoints.push(EndpointChange { change, endpoint: endpoint.clone(), files_added, files_removed });
+    }
+
+    // Usage in files outside the diff means the endpoint was used all along
+    let candidates: Vec<&Endpoint> = endpoints.iter()
+        .filter(|change| change.change != UsageChange::Changed)
+        .map(|change| &change.endpoint)
+        .collect();
+    let changed_paths: HashSet<&str> = changed.iter().map(|file| file.path.as_str()).collect();
+    let used_elsewhere = used_outside(scanner, files, dir, &candidates, &changed_paths, &scannable).await?;
+    for change in &mut endpoints {
+        if used_elsewhere.contains(&change.endpoint) {
+            change.change = UsageChange::Changed;
+        }
+    }
+
+    endpoints.sort_by(|a, b| {
+        (a.change, &a.endpoint.path, a.endpoint.method.as_str())
+            .cmp(&(b.change, &b.endpoint.path, b.endpoint.method.as_str()))
+    });
+
+    Ok(ChangeReport {
+        since: rev.to_string(),
+        changed_files: changed.iter().map(|file| dir.join(&file.path).to_string_lossy().to_string()).collect(),
+        endpoints,
+    })
+}
+
+fn usage_by_endpoint(scanned: Vec<(PathBuf, FileMatches)>) -> HashMap<Endpoint, BTreeSet<String>> {
+    let mut usage: HashMap<Endpoint, BTreeSet<String>> = HashMap::new();
+    for (path, matches) in scanned {
+        for endpoint in matches.into_keys() {
+            usage.entry(endpoint).or_default().insert(path.to_string_lossy().to_string());
+        }
+    }
+    usage
+}
+
+/// Scan `paths` (relative to `dir`) as they are in `version`.
+///
+/// Constants imported by the scanned files are looked up in the files they
+/// import, transitively, read from the same version. Missing, oversized and
+/// non-UTF-8 files are left out.
+async fn scan_version(
+    scanner: &ContentScanner,
+    files: &FileScanner,
+    dir: &Path,
+    version: Version<'_>,
+    paths: &[String],
+    scannable: &impl Fn(&Path) -> bool,
+) -> anyhow::Result<Vec<(PathBuf, FileMatches)>> {
+    let contents = read_files(dir, version, paths, files).await?;
+    let scanned: Vec<(PathBuf, String)> = paths.iter()
+        .zip(contents)
+        .filter_map(|(path, content)| Some((dir.join(path), content?)))
+        .collect();
+
+    let root = normalize(dir);
+    let mut seen: HashSet<PathBuf> = scanned.iter().map(|(path, _)| normalize(path)).collect();
+    let mut imported: Vec<(PathBuf, String)> = Vec::new();
+    // Files before `followed` have had their imports read
+    let mut followed = 0;
+    loop {
+        let mut wanted = Vec::new();
+        for (path, content) in scanned.iter().chain(&imported).skip(followed) {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            let module = match crate::syntax::parse_source(content, extension) {
+                Some(module) => module,
+                None => continue,
+            };
+            for import in &module.imports {
+                for candidate in module_candidates(path, &import.source) {
+                    let relative = match candidate.strip_prefix(&root) {
+                        Ok(relative) if scannable(relative) => relative.to_string_lossy().replace('\\', "/"),
+                        _ => continue,
+                    };
+                    if seen.insert(candidate) {
+                        wanted.push(relative);
+                    }
+                }
+            }
+        }
+        followed = scanned.len() + imported.len();
+        if wanted.is_empty() {
+            break;
+        }
+
+        let contents = read_files(dir, version, &wanted, files).await?;
+        imported.extend(wanted.iter().zip(contents).filter_map(|(path, content)| Some((dir.join(path), content?))));
+    }
+
+    let matches = scanner.scan_contents(&scanned, &imported);
+    Ok(scanned.into_iter().map(|(path, _)| path).zip(matches).collect())
+}
+
+/// Contents of `paths` (relative to `dir`) in `version`, in request order;
+/// `None` for missing, oversized and non-UTF-8 files
+async fn read_files(dir: &Path, version: Version<'_>, paths: &[String], files: &FileScanner) -> anyhow::Result<Vec<Option<String>>> {
+    match version {
+        Version::Base { rev, prefix } => {
+            let objects: Vec<String> = paths.iter().map(|path| format!("{}:{}{}", rev, prefix, path)).collect();
+            Ok(read_blobs(dir, &objects).await?
+                .into_iter()
+                .zip(paths)
+                .map(|(blob, path)| {
+                    blob.filter(|blob| files.accepts_size(&dir.join(path), blob.len() as u64))
+                        .and_then(|blob| String::from_utf8(blob).ok())
+                })
+                .collect())
+        }
+        Version::Worktree => Ok(paths.iter()
+            .map(|path| {
+                let path = dir.join(path);
+                let metadata = std::fs::metadata(&path).ok().filter(|metadata| metadata.is_file())?;
+                if !files.accepts_size(&path, metadata.len()) {
+                    return None;
+                }
+                std::fs::read_to_string(&path).ok()
+            })
+            .collect()),
+    }
+}
+
+/// Files changed between `rev` and the working tree, plus untracked files
+async fn changed_files(dir: &Path, rev: &str) -> anyhow::Result<Vec<ChangedFile>> {
+    let mut changed = Vec::new();
+
+    // Renames are split into a deletion and an addition so both sides get scanned
+    let diff = git(dir, &["diff", "--name-status", "-z", "--no-renames", "--relative", rev, "--"]).await?;
+    let mut fields = diff.split(|&b| b == 0).filter(|field| !field.is_empty());
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let path = String::from_utf8_lossy(path).to_string();
+        let (in_base, in_worktree) = match status.first() {
+            Some(b'A') => (false, true),
+            Some(b'D') => (true, false),
+            _ => (true, true),
+        };
+        changed.push(ChangedFile { path, in_base, in_worktree });
+    }
+
+    let untracked = git(dir, &["ls-files", "--others", "--exclude-standard", "-z"]).await?;
+    for path in untracked.split(|&b| b == 0).filter(|path| !path.is_empty()) {
+        let path = String::from_utf8_lossy(path).to_string();
+        changed.push(ChangedFile { path, in_base: false, in_worktree: true });
+    }
+
+    Ok(changed)
+}
+
+/// Endpoints among `endpoints` used by a file outside the diff.
+///
+/// `git grep` narrows the tree down to files containing one of the endpoints'
+/// search terms or naming a constant declared in such a file
+/// (`api.get(USERS_URL)`), and only those files are scanned. Endpoints
+/// without search terms can't be checked this way and count as used
+/// elsewhere, so they are never reported as newly used or unused.
+async fn used_outside(
+    scanner: &ContentScanner,
+    files: &FileScanner,
+    dir: &Path,
+    endpoints: &[&Endpoint],
+    changed: &HashSet<&str>,
+    scannable: &impl Fn(&Path) -> bool,
+) -> anyhow::Result<HashSet<Endpoint>> {
+    let mut used = HashSet::new();
+    let mut terms = Vec::new();
+    for endpoint in endpoints {
+        let endpoint_terms = scanner.search_terms(endpoint);
+        if endpoint_terms.is_empty() {
+            used.insert((*endpoint).clone());
+        }
+        terms.extend(endpoint_terms);
+    }
+    if terms.is_empty() {
+        return Ok(used);
+    }
+
+    let mut hits = grep_files(dir, &terms, false).await?;
+    let mut names = BTreeSet::new();
+    for (path, content) in hits.iter().zip(read_files(dir, Version::Worktree, &hits, files).await?) {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        if let Some(module) = content.and_then(|content| crate::syntax::parse_source(&content, extension)) {
+            names.extend(module.constants.into_iter().map(|(name, _)| name));
+        }
+    }
+    if !names.is_empty() {
+        hits.extend(grep_files(dir, &names.into_iter().collect::<Vec<_>>(), true).await?);
+    }
+    hits.sort();
+    hits.dedup();
+    hits.retain(|path| !changed.contains(path.as_str()) && scannable(Path::new(path)));
+
+    let wanted: HashSet<&Endpoint> = endpoints.iter().copied().collect();
+    for (_, matches) in scan_version(scanner, files, dir, Version::Worktree, &hits, scannable).await? {
+        used.extend(matches.into_keys().filter(|endpoint| wanted.contains(endpoint)));
+    }
+
+    Ok(used)
+}
+
+/// Tracked files under `dir` containing one of `terms`, relative to `dir`
+async fn grep_files(dir: &Path, terms: &[String], whole_words: bool) -> anyhow::Result<Vec<String>> {
+    let mut args = vec!["grep", "-l", "-z", "-F"];
+    if whole_words {
+        args.push("-w");
+    }
+    for term in terms {
+        args.push("-e");
+        args.push(term);
+    }
+    args.push("--");
+    let output = git_output(dir, &args).await?;
+    // Exit status 1 means nothing matched
+    if !output.status.success() && output.status.code() != Some(1) {
+        anyhow::bail!("git grep failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(output.stdout
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).to_string())
+        .collect())
+}
+
+/// Contents of git objects (`REV:PATH`) in request order; `None` for missing ones
+async fn read_blobs(dir: &Path, objects: &[String]) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut child = Command::new("git")
+        .current_dir(dir)
+        .args(["cat-file", "--batch"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}. Make sure git is installed.", e))?;
+
+    // Write requests concurrently with reading, so a full stdout pipe can't block git
+    let mut stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("Failed to open git stdin"))?;
+    let requests: String = objects.iter().map(|object| format!("{}\n", object)).collect();
+    let writer = tokio::spawn(async move { stdin.write_all(requests.as_bytes()).await });
+
+    let output = child.wait_with_output().await?;
+    writer.await??;
+    if !output.status.success() {
+        anyhow::bail!("git cat-file failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    parse_batch(&output.stdout, objects.len())
+}
+
+/// Split `git cat-file --batch` output into the contents of `count` objects.
+///
+/// Each object is `<oid> <type> <size>\n<content>\n`, or `<object> missing\n`
+/// where the object name may itself contain spaces (`HEAD:src/my file.ts`).
+fn parse_batch(mut rest: &[u8], count: usize) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
+    let mut blobs = Vec::with_capacity(count);
+    while blobs.len() < count {
+        let header_end = rest.iter().position(|&b| b == b'\n')
+            .ok_or_else(|| anyhow::anyhow!("Truncated git cat-file output"))?;
+        let header = String::from_utf8_lossy(&rest[..header_end]).to_string();
+        rest = &rest[header_end + 1..];
+
+        if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+            blobs.push(None);
+            continue;
+        }
+        let mut fields = header.rsplitn(3, ' ');
+        let (size, kind) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(size), Some(kind), Some(_oid)) => (size.parse::<usize>()?, kind),
+            _ => anyhow::bail!("Unexpected git cat-file header '{}'", header),
+        };
+        if rest.len() < size + 1 {
+            anyhow::bail!("Truncated git cat-file output");
+        }
+        blobs.push((kind == "blob").then(|| rest[..size].to_vec()));
+        rest = &rest[size + 1..];
+    }
+
+    Ok(blobs)
+}
+
+async fn git_output(dir: &Path, args: &[&str]) -> anyhow::Result<std::process::Output> {
+    Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}. Make sure git is installed.", e))
+}
+
+async fn git(dir: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let output = git_output(dir, args).await?;
+    if !output.status.success() {
+        anyhow::bail!("git {} failed: {}", args.first().unwrap_or(&""), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(output.stdout)
+}
+
+/// Print endpoint usage changes in the requested format
+pub fn print_changes(report: &ChangeReport, args: &CheckArgs) -> anyhow::Result<()> {
+    match args.output_format() {
+        OutputFormat::Table => print_table(report, args),
+        OutputFormat::Csv => print_csv(report),
+        OutputFormat::Json => print_json(report, args)?,
+        OutputFormat::Markdown => print_markdown(report),
+    }
+    Ok(())
+}
+
+fn print_table(report: &ChangeReport, args: &CheckArgs) {
+    print_header("Endpoint Usage Changes", &[
+        ("Since", report.since.clone()),
+        ("Search Dir", args.dir.display().to_string()),
+        ("Changed Files", report.changed_files.len().to_string()),
+    ]);
+
+    if report.endpoints.is_empty() {
+        println!("\nNo endpoint usage changed.");
+        return;
+    }
+
+    for (change, group) in grouped(&report.endpoints, |change| change.change) {
+        let marker = match change {
+            UsageChange::NewlyUsed => "+",
+            UsageChange::NewlyUnused => "-",
+            UsageChange::Changed => "~",
+        };
+        println!("\n{} ({}):", capitalize(change.as_str()), group.len());
+
+        for endpoint_change in group {
+            println!("  {} {} {}", marker, endpoint_change.endpoint.method.as_str(), endpoint_change.endpoint.path);
+            for file in &endpoint_change.files_added {
+                println!("      + {}", file);
+            }
+            for file in &endpoint_change.files_removed {
+                println!("      - {}", file);
+            }
+        }
+    }
+
+    let count = |kind| report.endpoints.iter().filter(|c| c.change == kind).count();
+    println!("\nSummary:");
+    println!("  Newly used: {}", count(UsageChange::NewlyUsed));
+    println!("  Newly unused: {}", count(UsageChange::NewlyUnused));
+    println!("  Changed: {}", count(UsageChange::Changed));
+}
+
+fn print_csv(report: &ChangeReport) {
+    println!("Change,Method,Endpoint,Files Added,Files Removed");
+    for change in &report.endpoints {
+        print_csv_row(&[
+            change.change.as_str(),
+            change.endpoint.method.as_str(),
+            &change.endpoint.path,
+            &change.files_added.join(";"),
+            &change.files_removed.join(";"),
+        ]);
+    }
+}
+
+fn print_json(report: &ChangeReport, args: &CheckArgs) -> anyhow::Result<()> {
+    use serde_json::json;
+
+    let items: Vec<serde_json::Value> = report.endpoints.iter()
+        .map(|change| json!({
+            "change": change.change.as_str(),
+            "endpoint": change.endpoint.path,
+            "method": change.endpoint.method.as_str(),
+            "files_added": change.files_added,
+            "files_removed": change.files_removed
+        }))
+        .collect();
+
+    let output = json!({
+        "since": {
+            "generated": chrono::Utc::now().to_rfc3339(),
+            "revision": report.since,
+            "search_dir": args.dir.to_string_lossy(),
+            "changed_files": report.changed_files
+        },
+        "changes": items
+    });
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_markdown(report: &ChangeReport) {
+    println!("## Endpoint usage changes since `{}`", report.since);
+    if report.endpoints.is_empty() {
+        println!("\nNo endpoint usage changed.");
+        return;
+    }
+
+    for (change, group) in grouped(&report.endpoints, |change| change.change) {
+        println!("\n### {} ({})\n", capitalize(change.as_str()), group.len());
+        print_markdown_header(&["Endpoint", "Methods", "Files Added", "Files Removed"]);
+        for endpoint_change in group {
+            println!("| {} | {} | {} | {} |",
+                     endpoint_change.endpoint.path,
+                     endpoint_change.endpoint.method.as_str(),
+                     markdown_list(&endpoint_change.files_added),
+                     markdown_list(&endpoint_change.files_removed));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_output_is_split_per_object() {
+        let output = concat!(
+            "HEAD:src/my file.ts missing\n",
+            "3b18e512dba79e4c8300dd08aeb37f8e728b8dad blob 13\n",
+            "fetch('/a')\n\n\n",
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904 tree 0\n\n",
+            "HEAD:lib missing\n",
+        );
+
+        let blobs = parse_batch(output.as_bytes(), 4).unwrap();
+        assert_eq!(blobs, [None, Some(b"fetch('/a')\n\n".to_vec()), None, None]);
+
+        assert!(parse_batch(b"3b18e512 blob 13\nfetch", 1).is_err());
+        assert!(parse_batch(b"unexpected\n", 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn blobs_are_read_from_git() {
+        let dir = std::env::temp_dir().join(format!("epcheck-changes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("my file.ts"), "fetch('/users');\n").unwrap();
+        git_output(&dir, &["init", "-q"]).await.unwrap();
+        git_output(&dir, &["add", "my file.ts"]).await.unwrap();
+
+        let objects = [":my file.ts".to_string(), ":no such file.ts".to_string()];
+        let blobs = read_blobs(&dir, &objects).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(blobs, [Some(b"fetch('/users');\n".to_vec()), None]);
+    }
+}
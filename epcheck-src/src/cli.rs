@@ -1,6 +1,9 @@
 use clap::{ArgEnum, Parser, Subcommand};
 use std::path::PathBuf;
 
+/// Files above this size are skipped unless `--max-file-size` says otherwise
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
 /// Fast OpenAPI endpoint usage checker
 #[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
@@ -20,18 +23,38 @@ pub struct CheckArgs {
     #[clap(short, long, value_name = "DIR", default_value = ".")]
     pub dir: PathBuf,
 
-    /// Output format
-    #[clap(short, long, arg_enum, default_value_t = OutputFormat::Table)]
-    pub format: OutputFormat,
+    /// Output format [default: table]
+    #[clap(short, long, arg_enum)]
+    pub format: Option<OutputFormat>,
 
     /// Filter endpoints by regex pattern
     #[clap(short, long, value_name = "PATTERN")]
     pub pattern: Option<String>,
 
+    /// Filter endpoints by OpenAPI tag (repeatable)
+    #[clap(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Group the report into sections
+    #[clap(long, arg_enum, value_name = "GROUP")]
+    pub group_by: Option<GroupBy>,
+
     /// Show only unused endpoints
     #[clap(long)]
     pub unused_only: bool,
 
+    /// Fail when deprecated endpoints are still used, listing the files that must migrate
+    #[clap(long)]
+    pub deprecation_audit: bool,
+
+    /// Also report HTTP calls whose path matches no spec endpoint (typos, removed endpoints), failing if there are any
+    #[clap(long)]
+    pub unknown: bool,
+
+    /// With --unknown, ignore calls to this host (`api.stripe.com`, `*.googleapis.com`; repeatable)
+    #[clap(long, value_name = "HOST", requires = "unknown")]
+    pub allow_host: Vec<String>,
+
     /// Show detailed file information
     #[clap(short, long)]
     pub verbose: bool,
@@ -52,11 +75,55 @@ pub struct CheckArgs {
     #[clap(long)]
     pub no_colors: bool,
 
-    /// Files to exclude from search
+    /// Files to exclude from search (gitignore-style globs)
     #[clap(short, long, value_name = "FILE")]
     pub exclude: Vec<String>,
+
+    /// Number of scanning threads [default: one per CPU]
+    #[clap(short = 'j', long, value_name = "N")]
+    pub threads: Option<usize>,
+
+    /// Skip files larger than this many bytes (minified bundles, data dumps); with --bundles, bundles with a `.map` next to them are scanned whatever their size
+    #[clap(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    pub max_file_size: u64,
+
+    /// Treat JS files that have a source map as built bundles and report each hit at its original source file and line
+    #[clap(long)]
+    pub bundles: bool,
+
+    /// With --bundles, also report hits that map to sources under node_modules
+    #[clap(long, requires = "bundles")]
+    pub bundle_node_modules: bool,
+
+    /// Report endpoints whose usage changed since a git revision, scanning only changed files
+    #[clap(long, value_name = "REV")]
+    pub since: Option<String>,
+
+    /// Rescan every file, ignoring and not updating the cache in DIR/.epcheck-cache
+    #[clap(long)]
+    pub no_cache: bool,
+
+    /// Project config file [default: .epcheck.toml in DIR or a parent directory]
+    #[clap(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective settings (config file merged with flags) and exit
+    #[clap(long)]
+    pub print_config: bool,
+
+    /// Settings from the project config file
+    #[clap(skip)]
+    pub project: crate::config::Config,
 }
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+
+impl CheckArgs {
+    pub fn output_format(&self) -> OutputFormat {
+        self.format.unwrap_or(OutputFormat::Table)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Table,
     Csv,
@@ -64,10 +131,57 @@ pub enum OutputFormat {
     Markdown,
 }
 
+/// Arguments for the validate command
+#[derive(Parser, Debug, Clone)]
+pub struct ValidateArgs {
+    /// Path or URL to OpenAPI specification file (JSON or YAML). If not provided, searches for common spec files in current and parent directories.
+    #[clap(short, long, value_name = "SPEC")]
+    pub spec: Option<String>,
+
+    /// Output format
+    #[clap(short, long, arg_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the diff command
+#[derive(Parser, Debug, Clone)]
+pub struct DiffArgs {
+    /// Old specification: file, URL or git revision (`REV:PATH`, e.g. `main:api/openapi.yaml`)
+    #[clap(value_name = "OLD")]
+    pub old: String,
+
+    /// New specification: file, URL or git revision (`REV:PATH`)
+    #[clap(value_name = "NEW")]
+    pub new: String,
+
+    /// Options for scanning code that references the old specification
+    #[clap(flatten)]
+    pub check: CheckArgs,
+}
+
+/// Arguments for the routes command
+#[derive(Parser, Debug, Clone)]
+pub struct RoutesArgs {
+    /// Options for finding the spec and scanning server code
+    #[clap(flatten)]
+    pub check: CheckArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum GroupBy {
+    Tag,
+}
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Check OpenAPI endpoint usage
     Check(CheckArgs),
+    /// Check the specification structurally (methods, path parameters, operationIds, refs, servers)
+    Validate(ValidateArgs),
+    /// Compare two spec versions and flag removed or changed endpoints still used in code
+    Diff(DiffArgs),
+    /// Compare server route declarations (Express, ASP.NET, FastAPI/Flask, Spring) with the spec
+    Routes(RoutesArgs),
     /// Generate shell completion scripts
     Completions {
         /// Shell to generate completions for
@@ -88,9 +202,52 @@ pub enum Shell {
     Elvish,
 }
 
-/// Load and parse OpenAPI specification from file or URL
-pub async fn load_openapi_spec(spec_path: &str) -> anyhow::Result<crate::openapi::OpenApiSpec> {
-    let content = if spec_path.starts_with("http://") || spec_path.starts_with("https://") {
+/// Whether a spec path refers to a remote document
+pub fn is_url(spec_path: &str) -> bool {
+    spec_path.starts_with("http://") || spec_path.starts_with("https://")
+}
+
+/// Split a `REV:PATH` git revision spec (e.g. `main:api/openapi.yaml`).
+///
+/// Existing local files always win, so Windows drive paths are never mistaken for revisions.
+pub fn git_revision(spec_path: &str) -> Option<(&str, &str)> {
+    if is_url(spec_path) || std::path::Path::new(spec_path).exists() {
+        return None;
+    }
+    spec_path.split_once(':').filter(|(rev, path)| !rev.is_empty() && !path.is_empty())
+}
+
+/// File a spec's relative `$ref`s are resolved against, and the git revision
+/// it is read at. URLs give an empty path: their relative refs can't be followed.
+pub fn spec_root(spec_path: &str) -> (&std::path::Path, Option<crate::resolver::Revision>) {
+    if is_url(spec_path) {
+        return (std::path::Path::new(""), None);
+    }
+    match git_revision(spec_path) {
+        Some((rev, path)) => {
+            let path = std::path::Path::new(path);
+            (path, Some(crate::resolver::Revision::new(rev, path)))
+        }
+        None => (std::path::Path::new(spec_path), None),
+    }
+}
+
+/// Read the raw contents of an OpenAPI specification from file, URL or git revision
+pub async fn read_spec_source(spec_path: &str) -> anyhow::Result<String> {
+    if let Some((rev, path)) = git_revision(spec_path) {
+        let output = tokio::process::Command::new("git")
+            .arg("show")
+            .arg(format!("{}:{}", rev, path))
+            .output()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to run git: {}. Make sure git is installed.", e))?;
+        if !output.status.success() {
+            anyhow::bail!("Failed to read {} from git: {}", spec_path, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        return Ok(String::from_utf8(output.stdout)?);
+    }
+
+    let content = if is_url(spec_path) {
         // Fetch from URL using curl
         let output = tokio::process::Command::new("curl")
             .arg("-s")
@@ -104,31 +261,29 @@ pub async fn load_openapi_spec(spec_path: &str) -> anyhow::Result<crate::openapi
         std::fs::read_to_string(spec_path)?
     };
 
+    Ok(content)
+}
+
+/// Load and parse OpenAPI specification from file or URL
+pub async fn load_openapi_spec(spec_path: &str) -> anyhow::Result<crate::openapi::OpenApiSpec> {
+    let content = read_spec_source(spec_path).await?;
+
     // Determine format from file extension or content
-    let extension = std::path::Path::new(spec_path)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("");
-
-    match extension {
-        "json" => {
-            let spec: crate::openapi::OpenApiSpec = serde_json::from_str(&content)?;
-            Ok(spec)
-        }
-        "yaml" | "yml" => {
-            let spec: crate::openapi::OpenApiSpec = serde_yaml::from_str(&content)?;
-            Ok(spec)
-        }
-        _ => {
-            // Try JSON first, then YAML
-            match serde_json::from_str(&content) {
-                Ok(spec) => Ok(spec),
-                Err(_) => {
-                    let spec: crate::openapi::OpenApiSpec = serde_yaml::from_str(&content)?;
-                    Ok(spec)
-                }
-            }
-        }
+    let path = std::path::Path::new(spec_path);
+    let document = crate::resolver::parse_document(&content, path)?;
+
+    // Inline $ref references, following relative file refs next to local and git specs
+    let (root_file, revision) = spec_root(spec_path);
+    let document = crate::resolver::resolve_refs(document, root_file, revision.as_ref())?;
+
+    crate::openapi::OpenApiSpec::from_document(document)
+}
+
+/// Use the given spec path or fall back to the closest auto-detected spec file
+pub fn resolve_spec_path(spec: &Option<String>) -> anyhow::Result<String> {
+    match spec {
+        Some(s) => Ok(s.clone()),
+        None => find_openapi_spec().ok_or_else(|| anyhow::anyhow!("No OpenAPI spec provided and none found in current or parent directories")),
     }
 }
 
@@ -0,0 +1,213 @@
+use crate::cli::{CheckArgs, OutputFormat};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the project config file, looked up from the scanned directory upwards
+pub const CONFIG_FILE_NAME: &str = ".epcheck.toml";
+
+/// Project settings from `.epcheck.toml`.
+///
+/// ```toml
+/// spec = "api/openapi.yaml"
+/// format = "markdown"
+/// include = ["src/**"]
+/// exclude = ["**/generated/**"]
+/// extensions = ["vue", "svelte"]
+/// allowed-hosts = ["api.stripe.com", "*.googleapis.com"]
+///
+/// [method-aliases]
+/// remove = "DELETE"
+///
+/// [[patterns]]
+/// pattern = "useApiQuery\\(\\s*['\"`]{path}"
+/// method = "GET"
+///
+/// [[patterns]]
+/// pattern = "this\\.http\\.request\\(\\s*'{method}'\\s*,\\s*'{path}'"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Spec location; relative paths are resolved against the config file's directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    /// Globs of files to scan; everything else is skipped
+    pub include: Vec<String>,
+    /// Globs of files to skip
+    pub exclude: Vec<String>,
+    /// Extra file extensions to scan
+    pub extensions: Vec<String>,
+    /// External hosts whose calls are never reported as unknown endpoints
+    pub allowed_hosts: Vec<String>,
+    /// Wrapper method names mapped to the HTTP method they send (`remove = "DELETE"`)
+    pub method_aliases: BTreeMap<String, String>,
+    pub patterns: Vec<CustomPattern>,
+    /// File the config was loaded from
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+/// A project-specific usage pattern.
+///
+/// `pattern` is a regex containing a `{path}` placeholder and, unless `method`
+/// is set, a `{method}` placeholder (HTTP method name or method alias).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomPattern {
+    pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        let mut config: Config = toml::from_str(&content)
+            .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+        config.source = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Find `.epcheck.toml` in `dir` or one of its parents
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        dir.ancestors()
+            .map(|ancestor| ancestor.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Spec location with relative paths resolved against the config file's
+    /// directory. Locations that don't exist there (URLs, `REV:PATH` git
+    /// revisions) are kept as written.
+    fn resolved_spec(&self) -> Option<String> {
+        let spec = self.spec.as_ref()?;
+        let next_to_config = self.source.as_ref()
+            .and_then(|source| source.parent())
+            .map(|base| base.join(spec))
+            .filter(|joined| !crate::cli::is_url(spec) && Path::new(spec).is_relative() && joined.exists());
+
+        Some(match next_to_config {
+            Some(path) => path.to_string_lossy().to_string(),
+            None => spec.clone(),
+        })
+    }
+}
+
+/// Merge the project config into the arguments; flags given on the command line win.
+///
+/// The config comes from `--config`, or else `.epcheck.toml` in the scanned
+/// directory or its parents.
+pub fn apply(mut args: CheckArgs) -> anyhow::Result<CheckArgs> {
+    let path = match &args.config {
+        Some(path) => Some(path.clone()),
+        None => Config::find(&args.dir),
+    };
+    let config = match path {
+        Some(path) => Config::load(&path)?,
+        None => return Ok(args),
+    };
+
+    if args.spec.is_none() {
+        args.spec = config.resolved_spec();
+    }
+    if args.format.is_none() {
+        args.format = config.format;
+    }
+    if args.exclude.is_empty() {
+        args.exclude = config.exclude.clone();
+    }
+    if args.allow_host.is_empty() {
+        args.allow_host = config.allowed_hosts.clone();
+    }
+    args.project = config;
+
+    Ok(args)
+}
+
+/// Print the effective settings after merging flags and config, as TOML
+pub fn print_effective(args: &CheckArgs) -> anyhow::Result<()> {
+    print!("{}", render_effective(args)?);
+    Ok(())
+}
+
+fn render_effective(args: &CheckArgs) -> anyhow::Result<String> {
+    let effective = Config {
+        spec: args.spec.clone(),
+        format: Some(args.output_format()),
+        exclude: args.exclude.clone(),
+        allowed_hosts: args.allow_host.clone(),
+        ..args.project.clone()
+    };
+
+    let mut rendered = match &args.project.source {
+        Some(source) => format!("# Loaded from {}\n", source.display()),
+        None => format!("# No {} found; showing defaults and flags\n", CONFIG_FILE_NAME),
+    };
+    rendered.push_str(&format!("# dir = {}\n", args.dir.display()));
+    // Going through `toml::Value` emits plain values before tables
+    rendered.push_str(&toml::to_string_pretty(&toml::Value::try_from(&effective)?)?);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A fresh temporary project with `.epcheck.toml` and `api/openapi.yaml`
+    fn project(name: &str, config: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("epcheck-config-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(dir.join("api")).unwrap();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE_NAME), config).unwrap();
+        std::fs::write(dir.join("api/openapi.yaml"), "openapi: 3.0.3\n").unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    const CONFIG: &str = r#"
+spec = "api/openapi.yaml"
+format = "markdown"
+exclude = ["**/generated/**"]
+allowed-hosts = ["api.stripe.com"]
+extensions = ["vue"]
+"#;
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let dir = project("apply", CONFIG);
+        let src = dir.join("src");
+        let args = CheckArgs::parse_from(["check", "-d", src.to_str().unwrap(), "--format", "json", "--unknown", "--allow-host", "example.com"]);
+        let args = apply(args).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Found in a parent of the scanned directory, with the spec resolved next to it
+        assert_eq!(args.project.source, Some(dir.join(CONFIG_FILE_NAME)));
+        assert_eq!(args.spec, Some(dir.join("api/openapi.yaml").to_string_lossy().to_string()));
+        assert_eq!(args.format, Some(OutputFormat::Json));
+        assert_eq!(args.exclude, ["**/generated/**"]);
+        assert_eq!(args.allow_host, ["example.com"]);
+        assert_eq!(args.project.extensions, ["vue"]);
+    }
+
+    #[test]
+    fn effective_settings_round_trip() {
+        let dir = project("effective", CONFIG);
+        let args = CheckArgs::parse_from(["check", "-d", dir.to_str().unwrap(), "--spec", "other.yaml", "--exclude", "dist/**"]);
+        let args = apply(args).unwrap();
+        let rendered = render_effective(&args).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(rendered.starts_with(&format!("# Loaded from {}\n", dir.join(CONFIG_FILE_NAME).display())));
+        let effective: Config = toml::from_str(&rendered).unwrap();
+        assert_eq!(effective.spec.as_deref(), Some("other.yaml"));
+        assert_eq!(effective.format, Some(OutputFormat::Markdown));
+        assert_eq!(effective.exclude, ["dist/**"]);
+        assert_eq!(effective.allowed_hosts, ["api.stripe.com"]);
+        assert_eq!(effective.extensions, ["vue"]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::paths::normalize;
+use crate::syntax::{Import, SourceFile};
+use crate::template::UrlTemplate;
+
+/// Longest chain of constants/imports followed for one reference
+const MAX_DEPTH: usize = 16;
+
+/// Extensions tried when resolving an import specifier to a scanned file
+const MODULE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
+#[derive(Debug, Default)]
+struct ModuleScope {
+    /// `None` marks a name declared more than once with different values
+    constants: HashMap<String, Option<UrlTemplate>>,
+    imports: HashMap<String, Import>,
+}
+
+/// String constants of every parsed file in the scanned set.
+///
+/// Resolves `api.get(USERS_URL)` where `USERS_URL` is declared in the same
+/// file or imported (`import { USERS_URL } from './routes'`,
+/// `import * as routes from './routes'`) from another scanned file.
+#[derive(Debug, Default)]
+pub struct ConstantTable {
+    modules: HashMap<PathBuf, ModuleScope>,
+    /// Files declaring each constant, for non-relative imports (`@/api/routes`)
+    by_name: HashMap<String, Vec<PathBuf>>,
+}
+
+impl ConstantTable {
+    pub fn add(&mut self, path: &Path, file: &SourceFile) {
+        let key = normalize(path);
+        let mut scope = ModuleScope::default();
+
+        for (name, value) in &file.constants {
+            scope.constants.entry(name.clone())
+                .and_modify(|existing| {
+                    if existing.as_ref() != Some(value) {
+                        *existing = None;
+                    }
+                })
+                .or_insert_with(|| Some(value.clone()));
+        }
+        for import in &file.imports {
+            scope.imports.insert(import.local.clone(), import.clone());
+        }
+
+        for (name, value) in &scope.constants {
+            if value.is_some() {
+                self.by_name.entry(name.clone()).or_default().push(key.clone());
+            }
+        }
+        self.modules.insert(key, scope);
+    }
+
+    /// Substitute every constant reference in `template`, as seen from `path`.
+    ///
+    /// References that don't name a known string constant stay dynamic.
+    pub fn resolve(&self, path: &Path, template: &UrlTemplate) -> UrlTemplate {
+        self.resolve_in(&normalize(path), template, 0)
+    }
+
+    fn resolve_in(&self, module: &Path, template: &UrlTemplate, depth: usize) -> UrlTemplate {
+        if !template.has_references() {
+            return template.clone();
+        }
+        template.resolve(|name| self.lookup(module, name, depth))
+    }
+
+    fn lookup(&self, module: &Path, name: &str, depth: usize) -> Option<UrlTemplate> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let scope = self.modules.get(module)?;
+
+        // `routes.USERS_URL` through `import * as routes from './routes'`
+        if let Some((namespace, member)) = name.split_once('.') {
+            let import = scope.imports.get(namespace).filter(|import| import.imported == "*")?;
+            return self.lookup_export(module, import, member, depth);
+        }
+
+        if let Some(value) = scope.constants.get(name) {
+            return value.as_ref().map(|value| self.resolve_in(module, value, depth + 1));
+        }
+
+        let import = scope.imports.get(name)?;
+        self.lookup_export(module, import, &import.imported, depth)
+    }
+
+    fn lookup_export(&self, importer: &Path, import: &Import, name: &str, depth: usize) -> Option<UrlTemplate> {
+        let target = match self.resolve_module(importer, &import.source) {
+            Some(target) => target,
+            // Path aliases and packages: accept a unique declaration in the scanned set
+            None => match self.by_name.get(name)?.as_slice() {
+                [only] => only.clone(),
+                _ => return None,
+            },
+        };
+        self.lookup(&target, name, depth + 1)
+    }
+
+    /// Scanned file a relative import specifier refers to
+    fn resolve_module(&self, importer: &Path, specifier: &str) -> Option<PathBuf> {
+        module_candidates(importer, specifier).into_iter().find(|candidate| self.modules.contains_key(candidate))
+    }
+}
+
+/// Normalized paths a relative import specifier may refer to, most likely
+/// first; empty for path aliases and packages
+pub fn module_candidates(importer: &Path, specifier: &str) -> Vec<PathBuf> {
+    let parent = match importer.parent() {
+        Some(parent) if specifier.starts_with('.') => parent,
+        _ => return Vec::new(),
+    };
+    let base = normalize(&parent.join(specifier));
+
+    let mut candidates = vec![base.clone()];
+    for extension in MODULE_EXTENSIONS {
+        candidates.push(PathBuf::from(format!("{}.{}", base.display(), extension)));
+        // ESM imports of TypeScript sources are written with `.js`
+        candidates.push(base.with_extension(extension));
+        candidates.push(base.join(format!("index.{}", extension)));
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse_source;
+
+    fn table(files: &[(&str, &str)]) -> ConstantTable {
+        let mut table = ConstantTable::default();
+        for (path, source) in files {
+            let path = Path::new(path);
+            let extension = path.extension().unwrap().to_str().unwrap();
+            table.add(path, &parse_source(source, extension).unwrap());
+        }
+        table
+    }
+
+    fn resolve(table: &ConstantTable, path: &str, name: &str) -> String {
+        table.resolve(Path::new(path), &UrlTemplate::reference(name)).to_string()
+    }
+
+    const ROUTES: (&str, &str) = ("src/api/routes.ts", "export const BASE = '/api';\nexport const USERS_URL = `${BASE}/users`;\n");
+
+    #[test]
+    fn constants_resolve_across_modules() {
+        let table = table(&[
+            ROUTES,
+            ("src/client.ts", "import { USERS_URL } from './api/routes';\n"),
+            ("src/pages/users.tsx", "import { USERS_URL as USERS } from '../api/routes.js';\n"),
+        ]);
+
+        assert_eq!(resolve(&table, "src/api/routes.ts", "USERS_URL"), "/api/users");
+        assert_eq!(resolve(&table, "src/client.ts", "USERS_URL"), "/api/users");
+        assert_eq!(resolve(&table, "src/pages/users.tsx", "USERS"), "/api/users");
+        assert_eq!(resolve(&table, "src/pages/users.tsx", "USERS_URL"), "{*}");
+    }
+
+    #[test]
+    fn namespace_imports_resolve_members() {
+        let table = table(&[
+            ROUTES,
+            ("src/client.ts", "import * as routes from './api/routes';\n"),
+        ]);
+
+        assert_eq!(resolve(&table, "src/client.ts", "routes.USERS_URL"), "/api/users");
+        assert_eq!(resolve(&table, "src/client.ts", "routes.MISSING"), "{*}");
+        assert_eq!(resolve(&table, "src/client.ts", "other.USERS_URL"), "{*}");
+    }
+
+    #[test]
+    fn path_aliases_need_a_unique_declaration() {
+        let unique = table(&[
+            ROUTES,
+            ("src/client.ts", "import { USERS_URL } from '@/api/routes';\n"),
+        ]);
+        assert_eq!(resolve(&unique, "src/client.ts", "USERS_URL"), "/api/users");
+
+        let ambiguous = table(&[
+            ROUTES,
+            ("src/admin/routes.ts", "export const USERS_URL = '/admin/users';\n"),
+            ("src/client.ts", "import { USERS_URL } from '@/api/routes';\n"),
+        ]);
+        assert_eq!(resolve(&ambiguous, "src/client.ts", "USERS_URL"), "{*}");
+    }
+}
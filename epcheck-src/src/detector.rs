@@ -0,0 +1,671 @@
+use regex::Regex;
+use std::sync::Arc;
+
+use crate::openapi::HttpMethod;
+use crate::syntax::snippet;
+use crate::template::{UrlPart, UrlTemplate};
+
+/// Longest call (from the opening parenthesis) examined for arguments
+const MAX_CALL_BYTES: usize = 4096;
+
+/// An HTTP client call found by a [`UsageDetector`]
+#[derive(Debug, Clone)]
+pub struct DetectedCall {
+    pub method: HttpMethod,
+    pub url: UrlTemplate,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+    pub snippet: String,
+    /// Label reported with each match (`csharp:httpclient`)
+    pub pattern: &'static str,
+}
+
+/// Finds HTTP client calls in the source files of one language.
+///
+/// Files whose extension has no detector (and no tree-sitter grammar) fall
+/// back to the generic regex patterns.
+pub trait UsageDetector: Send + Sync {
+    /// File extensions, without the dot, this detector handles
+    fn extensions(&self) -> &[&str];
+
+    fn detect(&self, content: &str) -> Vec<DetectedCall>;
+}
+
+/// Usage detectors selected by file extension.
+///
+/// The default registry holds the built-in C#, Python and Go detectors;
+/// library users can [`register`](Self::register) their own.
+#[derive(Clone)]
+pub struct DetectorRegistry {
+    detectors: Vec<Arc<dyn UsageDetector>>,
+}
+
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register(CSharpDetector::new())
+            .register(PythonDetector::new())
+            .register(GoDetector::new());
+        registry
+    }
+}
+
+impl DetectorRegistry {
+    /// A registry without any detectors
+    pub fn empty() -> Self {
+        Self { detectors: Vec::new() }
+    }
+
+    /// Add a detector; it takes precedence over earlier ones for its extensions
+    pub fn register(&mut self, detector: impl UsageDetector + 'static) -> &mut Self {
+        self.detectors.push(Arc::new(detector));
+        self
+    }
+
+    pub fn for_extension(&self, extension: &str) -> Option<&dyn UsageDetector> {
+        self.detectors.iter()
+            .rev()
+            .find(|detector| detector.extensions().contains(&extension))
+            .map(|detector| detector.as_ref())
+    }
+
+    /// Every extension some detector handles
+    pub fn extensions(&self) -> Vec<String> {
+        let mut extensions: Vec<String> = self.detectors.iter()
+            .flat_map(|detector| detector.extensions().iter().map(|ext| ext.to_string()))
+            .collect();
+        extensions.sort();
+        extensions.dedup();
+        extensions
+    }
+}
+
+/// Byte offset to line/column conversion for one file
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// 1-based line and column (in characters) of a byte offset
+    pub fn position(&self, content: &str, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let line_start = self.starts[line - 1];
+        (line, content[line_start..offset].chars().count() + 1)
+    }
+
+    /// Text of a 1-based line, without the line break
+    pub fn line_text<'a>(&self, content: &'a str, line: usize) -> &'a str {
+        let start = self.starts[line - 1];
+        let end = self.starts.get(line).map_or(content.len(), |&next| next - 1);
+        &content[start..end]
+    }
+}
+
+/// How a call pattern determines the HTTP method
+enum MethodSource {
+    /// The first capture group is the method name (`GetAsync`, `requests.post`)
+    Name,
+    /// The method is passed as the argument at this index (`http.NewRequest("DELETE", ...)`)
+    Argument(usize),
+}
+
+/// A regex locating a call up to its opening parenthesis
+struct CallPattern {
+    regex: Regex,
+    method: MethodSource,
+    url_argument: usize,
+    /// Required argument count, to tell client calls from same-named router methods
+    arity: Option<usize>,
+    label: &'static str,
+}
+
+impl CallPattern {
+    fn new(pattern: &str, method: MethodSource, url_argument: usize, label: &'static str) -> Self {
+        Self {
+            regex: Regex::new(pattern).expect("built-in detector pattern"),
+            method,
+            url_argument,
+            arity: None,
+            label,
+        }
+    }
+
+    fn with_arity(mut self, arity: usize) -> Self {
+        self.arity = Some(arity);
+        self
+    }
+}
+
+/// Run call patterns over a file, reading string operands with `string`
+fn detect_calls(
+    content: &str,
+    patterns: &[CallPattern],
+    string: fn(&str) -> Option<Vec<UrlPart>>,
+) -> Vec<DetectedCall> {
+    let lines = LineIndex::new(content);
+    let mut calls = Vec::new();
+
+    for pattern in patterns {
+        for captures in pattern.regex.captures_iter(content) {
+            let whole = match captures.get(0) {
+                Some(whole) => whole,
+                None => continue,
+            };
+            // Every pattern ends at the opening parenthesis
+            let body = match call_body(&content[whole.end() - 1..]) {
+                Some(body) => body,
+                None => continue,
+            };
+            let arguments = split_top_level(body, b',');
+            if pattern.arity.is_some_and(|arity| arity != arguments.len()) {
+                continue;
+            }
+
+            // Decorators register server routes (`@app.get("/users")`)
+            let (line, column) = lines.position(content, whole.start());
+            if lines.line_text(content, line).trim_start().starts_with('@') {
+                continue;
+            }
+
+            let method = match pattern.method {
+                MethodSource::Name => captures.get(1).and_then(|name| HttpMethod::from_str(name.as_str())),
+                MethodSource::Argument(index) => arguments.get(index).and_then(|arg| method_argument(arg)),
+            };
+            let url = arguments.get(pattern.url_argument)
+                .and_then(|arg| concatenation(strip_keyword(arg), string));
+
+            if let (Some(method), Some(url)) = (method, url) {
+                calls.push(DetectedCall {
+                    method,
+                    url,
+                    line,
+                    column,
+                    snippet: snippet(&content[whole.start()..]),
+                    pattern: pattern.label,
+                });
+            }
+        }
+    }
+
+    calls
+}
+
+/// `HttpClient` calls (`GetAsync`, `PostAsJsonAsync`, `GetFromJsonAsync<T>`) and
+/// `new HttpRequestMessage(HttpMethod.Delete, ...)`
+pub struct CSharpDetector {
+    patterns: Vec<CallPattern>,
+}
+
+impl CSharpDetector {
+    pub fn new() -> Self {
+        Self {
+            patterns: vec![
+                CallPattern::new(
+                    r"\.\s*(Get|Post|Put|Patch|Delete)(?:String|Stream|ByteArray|FromJson|AsJson)?Async\s*(?:<[^()]*>)?\s*\(",
+                    MethodSource::Name,
+                    0,
+                    "csharp:httpclient",
+                ),
+                CallPattern::new(
+                    r"\bnew\s+HttpRequestMessage\s*\(",
+                    MethodSource::Argument(0),
+                    1,
+                    "csharp:request-message",
+                ),
+            ],
+        }
+    }
+}
+
+impl Default for CSharpDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageDetector for CSharpDetector {
+    fn extensions(&self) -> &[&str] {
+        &["cs"]
+    }
+
+    fn detect(&self, content: &str) -> Vec<DetectedCall> {
+        detect_calls(content, &self.patterns, csharp_string)
+    }
+}
+
+/// `requests`/`httpx`-style calls (`requests.post(...)`, `client.get(...)`,
+/// `session.request("DELETE", ...)`)
+pub struct PythonDetector {
+    patterns: Vec<CallPattern>,
+}
+
+impl PythonDetector {
+    pub fn new() -> Self {
+        Self {
+            patterns: vec![
+                CallPattern::new(
+                    r"\.\s*(get|post|put|patch|delete|head|options)\s*\(",
+                    MethodSource::Name,
+                    0,
+                    "python:client",
+                ),
+                CallPattern::new(
+                    r"\.\s*request\s*\(",
+                    MethodSource::Argument(0),
+                    1,
+                    "python:request",
+                ),
+            ],
+        }
+    }
+}
+
+impl Default for PythonDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageDetector for PythonDetector {
+    fn extensions(&self) -> &[&str] {
+        &["py"]
+    }
+
+    fn detect(&self, content: &str) -> Vec<DetectedCall> {
+        detect_calls(content, &self.patterns, python_string)
+    }
+}
+
+/// `net/http` calls (`http.Get(url)`, `client.Post(...)`,
+/// `http.NewRequest("DELETE", url+"/users/"+id, nil)`)
+pub struct GoDetector {
+    patterns: Vec<CallPattern>,
+}
+
+impl GoDetector {
+    pub fn new() -> Self {
+        Self {
+            patterns: vec![
+                // Arity rules out router registrations such as chi's `r.Get("/users", handler)`
+                CallPattern::new(r"\.\s*(Get|Head)\s*\(", MethodSource::Name, 0, "go:client").with_arity(1),
+                CallPattern::new(r"\.\s*(Post)\s*\(", MethodSource::Name, 0, "go:client").with_arity(3),
+                CallPattern::new(r"\.\s*(Post)Form\s*\(", MethodSource::Name, 0, "go:client").with_arity(2),
+                CallPattern::new(r"\bhttp\.NewRequest\s*\(", MethodSource::Argument(0), 1, "go:new-request"),
+                CallPattern::new(
+                    r"\bhttp\.NewRequestWithContext\s*\(",
+                    MethodSource::Argument(1),
+                    2,
+                    "go:new-request",
+                ),
+            ],
+        }
+    }
+}
+
+impl Default for GoDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageDetector for GoDetector {
+    fn extensions(&self) -> &[&str] {
+        &["go"]
+    }
+
+    fn detect(&self, content: &str) -> Vec<DetectedCall> {
+        detect_calls(content, &self.patterns, go_string)
+    }
+}
+
+/// C# `"..."`, `@"..."` and interpolated `$"{baseUrl}/users/{id}"` strings
+fn csharp_string(operand: &str) -> Option<Vec<UrlPart>> {
+    let quote = operand.find('"')?;
+    let prefix = &operand[..quote];
+    if !matches!(prefix, "" | "$" | "@" | "$@" | "@$") {
+        return None;
+    }
+    let body = operand[quote..].strip_prefix('"')?.strip_suffix('"')?;
+
+    if prefix.contains('$') {
+        Some(interpolated(body))
+    } else {
+        Some(vec![UrlPart::Literal(body.to_string())])
+    }
+}
+
+/// Python strings, including f-strings (`f"{base}/users"`)
+fn python_string(operand: &str) -> Option<Vec<UrlPart>> {
+    let quote_index = operand.find(['"', '\''])?;
+    let prefix = operand[..quote_index].to_ascii_lowercase();
+    if prefix.len() > 2 || !prefix.chars().all(|c| matches!(c, 'f' | 'r' | 'b' | 'u')) {
+        return None;
+    }
+
+    let quote = &operand[quote_index..quote_index + 1];
+    let body = operand[quote_index..].strip_prefix(quote)?.strip_suffix(quote)?;
+
+    if prefix.contains('f') {
+        Some(interpolated(body))
+    } else {
+        Some(vec![UrlPart::Literal(body.to_string())])
+    }
+}
+
+/// Go `"..."` and raw strings, plus `fmt.Sprintf("%s/users/%d", base, id)`
+fn go_string(operand: &str) -> Option<Vec<UrlPart>> {
+    let quoted = operand.strip_prefix('"').and_then(|s| s.strip_suffix('"'))
+        .or_else(|| operand.strip_prefix('`').and_then(|s| s.strip_suffix('`')));
+    if let Some(body) = quoted {
+        return Some(vec![UrlPart::Literal(body.to_string())]);
+    }
+
+    let rest = operand.strip_prefix("fmt.Sprintf")?.trim_start();
+    let format = split_top_level(call_body(rest)?, b',').into_iter().next()?;
+    let format = format.strip_prefix('"')?.strip_suffix('"')?;
+    Some(format_verbs(format))
+}
+
+/// Split `{expr}` interpolations (C# `$"..."`, Python f-strings) into dynamic parts
+fn interpolated(body: &str) -> Vec<UrlPart> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                parts.push(UrlPart::Literal(std::mem::take(&mut literal)));
+                parts.push(UrlPart::Dynamic);
+                let mut depth = 1;
+                for c in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            c => literal.push(c),
+        }
+    }
+
+    parts.push(UrlPart::Literal(literal));
+    parts
+}
+
+/// Split printf-style `%s`/`%d`/`%v` verbs into dynamic parts
+fn format_verbs(format: &str) -> Vec<UrlPart> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+        } else if chars.peek() == Some(&'%') {
+            chars.next();
+            literal.push('%');
+        } else {
+            parts.push(UrlPart::Literal(std::mem::take(&mut literal)));
+            parts.push(UrlPart::Dynamic);
+            // Skip flags and width up to the verb letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+
+    parts.push(UrlPart::Literal(literal));
+    parts
+}
+
+/// Parse `a + "b" + c` into a template, reading string operands with `string`.
+///
+/// Returns `None` unless at least one operand is a string.
+fn concatenation(expression: &str, string: fn(&str) -> Option<Vec<UrlPart>>) -> Option<UrlTemplate> {
+    let mut parts = Vec::new();
+    let mut has_string = false;
+
+    for operand in split_top_level(expression, b'+') {
+        match string(operand) {
+            Some(operand_parts) => {
+                has_string = true;
+                parts.extend(operand_parts);
+            }
+            None => parts.push(UrlPart::Dynamic),
+        }
+    }
+
+    has_string.then(|| UrlTemplate::new(parts))
+}
+
+/// HTTP method passed as an argument: `"DELETE"`, `HttpMethod.Delete`,
+/// `new HttpMethod("PATCH")` or `http.MethodDelete`
+fn method_argument(argument: &str) -> Option<HttpMethod> {
+    let argument = strip_keyword(argument);
+
+    let name = match argument.find(['"', '\'']) {
+        Some(start) => {
+            let rest = &argument[start + 1..];
+            &rest[..rest.find(['"', '\''])?]
+        }
+        None => {
+            let last = argument.rsplit('.').next()?;
+            last.strip_prefix("Method").unwrap_or(last)
+        }
+    };
+
+    HttpMethod::from_str(name)
+}
+
+/// Drop a keyword-argument name (`url=...`, `method="GET"`)
+fn strip_keyword(argument: &str) -> &str {
+    let argument = argument.trim();
+    let name_len = argument.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(0);
+    let rest = argument[name_len..].trim_start();
+
+    if name_len > 0 && rest.starts_with('=') && !rest.starts_with("==") {
+        rest[1..].trim_start()
+    } else {
+        argument
+    }
+}
+
+/// Text between a leading `(` and its matching `)`
+fn call_body(text: &str) -> Option<&str> {
+    if !text.starts_with('(') {
+        return None;
+    }
+
+    let limit = text.len().min(MAX_CALL_BYTES);
+    TopLevel::new(&text.as_bytes()[..limit])
+        .find(|&(_, byte, depth)| byte == b')' && depth == 1)
+        .map(|(index, _, _)| &text[1..index])
+}
+
+/// Split on `separator` outside strings and brackets, trimming each piece
+fn split_top_level(text: &str, separator: u8) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+
+    for (index, byte, depth) in TopLevel::new(text.as_bytes()) {
+        if byte == separator && depth == 0 {
+            pieces.push(text[start..index].trim());
+            start = index + 1;
+        }
+    }
+
+    pieces.push(text[start..].trim());
+    pieces
+}
+
+/// Bytes outside string literals, with the bracket depth before each byte
+struct TopLevel<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    depth: usize,
+    quote: Option<u8>,
+}
+
+impl<'a> TopLevel<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, index: 0, depth: 0, quote: None }
+    }
+}
+
+impl Iterator for TopLevel<'_> {
+    type Item = (usize, u8, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.bytes.len() {
+            let (index, byte) = (self.index, self.bytes[self.index]);
+            self.index += 1;
+
+            if let Some(quote) = self.quote {
+                if byte == b'\\' && quote != b'`' {
+                    self.index += 1;
+                } else if byte == quote {
+                    self.quote = None;
+                }
+                continue;
+            }
+
+            let depth = self.depth;
+            match byte {
+                b'"' | b'\'' | b'`' => {
+                    self.quote = Some(byte);
+                    continue;
+                }
+                b'(' | b'[' | b'{' => self.depth += 1,
+                b')' | b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            return Some((index, byte, depth));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `METHOD url` of every detected call, in source order
+    fn calls(detector: &dyn UsageDetector, content: &str) -> Vec<String> {
+        let mut calls = detector.detect(content);
+        calls.sort_by_key(|call| (call.line, call.column));
+        calls.iter().map(|call| format!("{} {}", call.method.as_str(), call.url)).collect()
+    }
+
+    #[test]
+    fn csharp_calls() {
+        let content = r#"
+            var user = await client.GetFromJsonAsync<User>($"{baseUrl}/users/{id}");
+            await client.PostAsJsonAsync("/users", user);
+            var request = new HttpRequestMessage(HttpMethod.Delete, "/users/" + id);
+            var patch = new HttpRequestMessage(new HttpMethod("PATCH"), @"/users");
+            await client.SendAsync(request);
+        "#;
+
+        assert_eq!(calls(&CSharpDetector::new(), content), [
+            "GET {*}/users/{*}",
+            "POST /users",
+            "DELETE /users/{*}",
+            "PATCH /users",
+        ]);
+    }
+
+    #[test]
+    fn python_calls_skip_decorators() {
+        let content = r#"
+@app.get("/users")
+def list_users():
+    requests.post(url=f"{BASE}/users", json=body)
+    session.request("DELETE", "/users/" + str(user_id))
+    httpx.get(url)
+"#;
+
+        assert_eq!(calls(&PythonDetector::new(), content), [
+            "POST {*}/users",
+            "DELETE /users/{*}",
+        ]);
+    }
+
+    #[test]
+    fn go_calls_need_the_client_arity() {
+        let content = r#"
+            resp, err := http.Get(base + "/users")
+            r.Get("/users", listUsers)
+            http.Post(base+"/users", "application/json", body)
+            r.Post("/users", createUser)
+            http.PostForm("/login", values)
+            req, _ := http.NewRequest(http.MethodDelete, fmt.Sprintf("%s/users/%d", base, id), nil)
+            req, _ = http.NewRequestWithContext(ctx, "PUT", `/users/1`, body)
+        "#;
+
+        assert_eq!(calls(&GoDetector::new(), content), [
+            "GET {*}/users",
+            "POST {*}/users",
+            "POST /login",
+            "DELETE {*}/users/{*}",
+            "PUT /users/1",
+        ]);
+    }
+
+    #[test]
+    fn detected_calls_carry_their_position() {
+        let calls = GoDetector::new().detect("package api\n\nfunc f() {\n\thttp.Get(\"/users\")\n}\n");
+        // The column is where the pattern starts matching, at `.Get`
+        assert_eq!((calls[0].line, calls[0].column, calls[0].pattern), (4, 6, "go:client"));
+    }
+
+    #[test]
+    fn call_bodies_end_at_the_matching_parenthesis() {
+        assert_eq!(call_body(r#"("/a)", f(b), [c]) + rest"#), Some(r#""/a)", f(b), [c]"#));
+        assert_eq!(call_body("(a, (b)"), None);
+        assert_eq!(call_body("a)"), None);
+    }
+
+    #[test]
+    fn top_level_skips_strings_and_tracks_depth() {
+        let structure: Vec<(u8, usize)> = TopLevel::new(br#"f("(", [a], '\'')"#)
+            .map(|(_, byte, depth)| (byte, depth))
+            .filter(|(byte, _)| !byte.is_ascii_alphanumeric() && *byte != b' ')
+            .collect();
+        assert_eq!(structure, [(b'(', 0), (b',', 1), (b'[', 1), (b']', 2), (b',', 1), (b')', 1)]);
+
+        assert_eq!(split_top_level(r#""a,b", f(c, d), `e,f`"#, b','), [r#""a,b""#, "f(c, d)", "`e,f`"]);
+    }
+
+    #[test]
+    fn keyword_names_are_stripped() {
+        assert_eq!(strip_keyword(" url=f'/users' "), "f'/users'");
+        assert_eq!(strip_keyword("method = 'GET'"), "'GET'");
+        assert_eq!(strip_keyword("a == b"), "a == b");
+        assert_eq!(strip_keyword("'/users'"), "'/users'");
+    }
+}
@@ -0,0 +1,472 @@
+use crate::analyzer::AnalysisResult;
+use crate::cli::{DiffArgs, OutputFormat};
+use crate::openapi::{Endpoint, HttpMethod, OpenApiSpec, Operation, ParameterLocation};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::report::{capitalize, grouped, markdown_list, print_csv_row, print_header, print_markdown_header};
+use std::collections::{HashMap, HashSet};
+
+/// A `{name}` parameter in a path template
+static PATH_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{[^}]+\}").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Removed,
+    Changed,
+    Added,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+            Self::Added => "added",
+        }
+    }
+}
+
+/// One difference within an operation present in both specs
+#[derive(Debug, Clone)]
+pub struct ChangeDetail {
+    pub description: String,
+    pub breaking: bool,
+}
+
+impl ChangeDetail {
+    fn breaking(description: String) -> Self {
+        Self { description, breaking: true }
+    }
+
+    fn compatible(description: String) -> Self {
+        Self { description, breaking: false }
+    }
+}
+
+/// An added, removed or changed operation between two spec versions
+#[derive(Debug, Clone)]
+pub struct OperationChange {
+    pub kind: ChangeKind,
+    /// The endpoint as defined in the old spec (the new one for additions)
+    pub endpoint: Endpoint,
+    pub details: Vec<ChangeDetail>,
+    /// Files that still reference the old endpoint
+    pub files: Vec<String>,
+}
+
+impl OperationChange {
+    /// Removals are always breaking; changes are breaking when any detail is
+    pub fn is_breaking(&self) -> bool {
+        match self.kind {
+            ChangeKind::Removed => true,
+            ChangeKind::Changed => self.details.iter().any(|d| d.breaking),
+            ChangeKind::Added => false,
+        }
+    }
+}
+
+/// Compute added, removed and changed operations between two specs.
+///
+/// Operations are matched on method and path first. Those left over are
+/// matched on method and path template, ignoring parameter names, so renaming
+/// `{id}` to `{userId}` is reported as a change rather than a removal plus an
+/// addition, as long as only one operation on each side has that template.
+pub fn diff_specs(old: &OpenApiSpec, new: &OpenApiSpec) -> Vec<OperationChange> {
+    let mut old_ops: Vec<(Endpoint, &Operation)> = old.operations().collect();
+    let mut new_ops: Vec<(Endpoint, &Operation)> = new.operations().collect();
+    let mut pairs = Vec::new();
+
+    old_ops.retain(|(old_endpoint, old_op)| {
+        match new_ops.iter().position(|(new_endpoint, _)| new_endpoint == old_endpoint) {
+            Some(index) => {
+                let (new_endpoint, new_op) = new_ops.swap_remove(index);
+                pairs.push((old_endpoint.clone(), *old_op, new_endpoint, new_op));
+                false
+            }
+            None => true,
+        }
+    });
+
+    let old_templates = template_counts(&old_ops);
+    let new_templates = template_counts(&new_ops);
+    old_ops.retain(|(old_endpoint, old_op)| {
+        let key = renamed_key(old_endpoint);
+        if old_templates[&key] != 1 || new_templates.get(&key) != Some(&1) {
+            return true;
+        }
+        let index = new_ops.iter().position(|(new_endpoint, _)| renamed_key(new_endpoint) == key).unwrap();
+        let (new_endpoint, new_op) = new_ops.swap_remove(index);
+        pairs.push((old_endpoint.clone(), *old_op, new_endpoint, new_op));
+        false
+    });
+
+    let mut changes = Vec::new();
+
+    for (old_endpoint, _) in old_ops {
+        changes.push(OperationChange {
+            kind: ChangeKind::Removed,
+            endpoint: old_endpoint,
+            details: Vec::new(),
+            files: Vec::new(),
+        });
+    }
+
+    for (old_endpoint, old_op, new_endpoint, new_op) in pairs {
+        let mut details = Vec::new();
+        if old_endpoint.path != new_endpoint.path {
+            details.push(ChangeDetail::compatible(format!("path parameters renamed: {}", new_endpoint.path)));
+        }
+        details.extend(diff_operations(old_op, new_op));
+
+        if !details.is_empty() {
+            changes.push(OperationChange {
+                kind: ChangeKind::Changed,
+                endpoint: old_endpoint,
+                details,
+                files: Vec::new(),
+            });
+        }
+    }
+
+    for (new_endpoint, _) in new_ops {
+        changes.push(OperationChange {
+            kind: ChangeKind::Added,
+            endpoint: new_endpoint,
+            details: Vec::new(),
+            files: Vec::new(),
+        });
+    }
+
+    changes.sort_by(|a, b| {
+        (a.kind, &a.endpoint.path, a.endpoint.method.as_str())
+            .cmp(&(b.kind, &b.endpoint.path, b.endpoint.method.as_str()))
+    });
+    changes
+}
+
+/// Method and path template with parameter names erased
+fn renamed_key(endpoint: &Endpoint) -> (String, HttpMethod) {
+    (template_key(&endpoint.path), endpoint.method)
+}
+
+/// Number of operations sharing each method and path template
+fn template_counts(ops: &[(Endpoint, &Operation)]) -> HashMap<(String, HttpMethod), usize> {
+    let mut counts = HashMap::new();
+    for (endpoint, _) in ops {
+        *counts.entry(renamed_key(endpoint)).or_default() += 1;
+    }
+    counts
+}
+
+/// Compare two versions of the same operation
+fn diff_operations(old: &Operation, new: &Operation) -> Vec<ChangeDetail> {
+    let mut details = Vec::new();
+
+    // Parameters (path parameters are compared through the path template)
+    let params = |op: &Operation| -> HashMap<(String, ParameterLocation), bool> {
+        op.parameters.iter()
+            .filter(|p| p.location != ParameterLocation::Path)
+            .map(|p| ((p.name.clone(), p.location), p.required))
+            .collect()
+    };
+    let old_params = params(old);
+    let new_params = params(new);
+
+    for ((name, location), required) in &new_params {
+        let location_name = location_str(*location);
+        match old_params.get(&(name.clone(), *location)) {
+            None if *required => details.push(ChangeDetail::breaking(format!("required {} parameter '{}' added", location_name, name))),
+            None => details.push(ChangeDetail::compatible(format!("optional {} parameter '{}' added", location_name, name))),
+            Some(false) if *required => details.push(ChangeDetail::breaking(format!("{} parameter '{}' became required", location_name, name))),
+            _ => {}
+        }
+    }
+    for (name, location) in old_params.keys() {
+        if !new_params.contains_key(&(name.clone(), *location)) {
+            details.push(ChangeDetail::compatible(format!("{} parameter '{}' removed", location_str(*location), name)));
+        }
+    }
+
+    // Request body
+    match (&old.request_body, &new.request_body) {
+        (None, Some(body)) if body.required => details.push(ChangeDetail::breaking("required request body added".to_string())),
+        (None, Some(_)) => details.push(ChangeDetail::compatible("optional request body added".to_string())),
+        (Some(_), None) => details.push(ChangeDetail::compatible("request body removed".to_string())),
+        (Some(old_body), Some(new_body)) => {
+            if new_body.required && !old_body.required {
+                details.push(ChangeDetail::breaking("request body became required".to_string()));
+            }
+            let new_media: HashSet<&String> = new_body.content.keys().collect();
+            let mut removed: Vec<&String> = old_body.content.keys().filter(|m| !new_media.contains(m)).collect();
+            removed.sort();
+            for media in removed {
+                details.push(ChangeDetail::breaking(format!("request media type '{}' removed", media)));
+            }
+        }
+        (None, None) => {}
+    }
+
+    // Success responses clients may depend on
+    let mut removed_responses: Vec<&String> = old.responses.keys()
+        .filter(|code| code.starts_with('2') && !new.responses.contains_key(*code))
+        .collect();
+    removed_responses.sort();
+    for code in removed_responses {
+        details.push(ChangeDetail::breaking(format!("response {} removed", code)));
+    }
+
+    // Generated clients derive their method names from the operationId
+    if old.operation_id.is_some() && old.operation_id != new.operation_id {
+        details.push(ChangeDetail::breaking(format!(
+            "operationId changed from '{}' to '{}'",
+            old.operation_id.as_deref().unwrap_or(""),
+            new.operation_id.as_deref().unwrap_or("")
+        )));
+    }
+
+    if new.deprecated && !old.deprecated {
+        details.push(ChangeDetail::compatible("operation deprecated".to_string()));
+    }
+
+    details
+}
+
+/// Attach the files referencing each removed or changed endpoint
+pub fn attach_usage(changes: &mut [OperationChange], usage: &AnalysisResult) {
+    let files: HashMap<&Endpoint, &Vec<String>> = usage.endpoints.iter()
+        .map(|result| (&result.endpoint, &result.files))
+        .collect();
+
+    for change in changes.iter_mut().filter(|c| c.kind != ChangeKind::Added) {
+        if let Some(found) = files.get(&change.endpoint) {
+            change.files = (*found).clone();
+        }
+    }
+}
+
+/// Path template with parameter names erased (`/users/{id}` -> `/users/{}`)
+pub(crate) fn template_key(path: &str) -> String {
+    PATH_PARAM.replace_all(path, "{}").to_string()
+}
+
+fn location_str(location: ParameterLocation) -> &'static str {
+    match location {
+        ParameterLocation::Path => "path",
+        ParameterLocation::Query => "query",
+        ParameterLocation::Header => "header",
+        ParameterLocation::Cookie => "cookie",
+        ParameterLocation::Body => "body",
+        ParameterLocation::FormData => "formData",
+    }
+}
+
+/// Print the spec diff in the requested format
+pub fn print_diff(changes: &[OperationChange], args: &DiffArgs) -> anyhow::Result<()> {
+    match args.check.output_format() {
+        OutputFormat::Table => print_table(changes, args),
+        OutputFormat::Csv => print_csv(changes),
+        OutputFormat::Json => print_json(changes, args)?,
+        OutputFormat::Markdown => print_markdown(changes, args),
+    }
+    Ok(())
+}
+
+fn print_table(changes: &[OperationChange], args: &DiffArgs) {
+    print_header("OpenAPI Spec Diff", &[
+        ("Old Spec", args.old.clone()),
+        ("New Spec", args.new.clone()),
+        ("Search Dir", args.check.dir.display().to_string()),
+    ]);
+
+    for (kind, group) in grouped(changes, |change| change.kind) {
+        let marker = match kind {
+            ChangeKind::Removed => "-",
+            ChangeKind::Changed => "~",
+            ChangeKind::Added => "+",
+        };
+        println!("\n{} ({}):", capitalize(kind.as_str()), group.len());
+
+        for change in group {
+            let breaking = if change.is_breaking() { " [BREAKING]" } else { "" };
+            let in_use = if change.files.is_empty() {
+                String::new()
+            } else {
+                format!(" [used in {} files]", change.files.len())
+            };
+            println!("  {} {} {}{}{}", marker, change.endpoint.method.as_str(), change.endpoint.path, breaking, in_use);
+
+            for detail in &change.details {
+                let flag = if detail.breaking { " (breaking)" } else { "" };
+                println!("      * {}{}", detail.description, flag);
+            }
+            for file in &change.files {
+                println!("      - {}", file);
+            }
+        }
+    }
+
+    let breaking_in_use = changes.iter().filter(|c| c.is_breaking() && !c.files.is_empty()).count();
+    println!("\nSummary:");
+    println!("  Added: {}", changes.iter().filter(|c| c.kind == ChangeKind::Added).count());
+    println!("  Removed: {}", changes.iter().filter(|c| c.kind == ChangeKind::Removed).count());
+    println!("  Changed: {}", changes.iter().filter(|c| c.kind == ChangeKind::Changed).count());
+    println!("  Breaking changes in use: {}", breaking_in_use);
+}
+
+fn print_csv(changes: &[OperationChange]) {
+    println!("Change,Method,Endpoint,Breaking,Details,Files");
+    for change in changes {
+        let details: Vec<&str> = change.details.iter().map(|d| d.description.as_str()).collect();
+        print_csv_row(&[
+            change.kind.as_str(),
+            change.endpoint.method.as_str(),
+            &change.endpoint.path,
+            &change.is_breaking().to_string(),
+            &details.join(";"),
+            &change.files.join(";"),
+        ]);
+    }
+}
+
+fn print_json(changes: &[OperationChange], args: &DiffArgs) -> anyhow::Result<()> {
+    use serde_json::json;
+
+    let items: Vec<serde_json::Value> = changes.iter()
+        .map(|change| json!({
+            "change": change.kind.as_str(),
+            "endpoint": change.endpoint.path,
+            "method": change.endpoint.method.as_str(),
+            "breaking": change.is_breaking(),
+            "details": change.details.iter()
+                .map(|d| json!({ "description": d.description, "breaking": d.breaking }))
+                .collect::<Vec<_>>(),
+            "files": change.files
+        }))
+        .collect();
+
+    let output = json!({
+        "diff": {
+            "generated": chrono::Utc::now().to_rfc3339(),
+            "old_spec": args.old,
+            "new_spec": args.new,
+            "search_dir": args.check.dir.to_string_lossy(),
+            "breaking_in_use": changes.iter().filter(|c| c.is_breaking() && !c.files.is_empty()).count()
+        },
+        "changes": items
+    });
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_markdown(changes: &[OperationChange], args: &DiffArgs) {
+    println!("## OpenAPI diff: `{}` → `{}`", args.old, args.new);
+
+    for (kind, group) in grouped(changes, |change| change.kind) {
+        println!("\n### {} ({})\n", capitalize(kind.as_str()), group.len());
+        print_markdown_header(&["Endpoint", "Methods", "Breaking", "Details", "Files"]);
+        for change in group {
+            let details: Vec<String> = change.details.iter().map(|d| d.description.clone()).collect();
+            println!("| {} | {} | {} | {} | {} |",
+                     change.endpoint.path,
+                     change.endpoint.method.as_str(),
+                     if change.is_breaking() { "yes" } else { "no" },
+                     markdown_list(&details),
+                     markdown_list(&change.files));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn operation(operation: serde_json::Value) -> Operation {
+        serde_json::from_value(operation).unwrap()
+    }
+
+    fn spec(paths: serde_json::Value) -> OpenApiSpec {
+        OpenApiSpec::from_document(json!({"openapi": "3.0.3", "paths": paths})).unwrap()
+    }
+
+    fn summary(changes: &[OperationChange]) -> Vec<(ChangeKind, String)> {
+        changes.iter().map(|change| (change.kind, change.endpoint.to_string())).collect()
+    }
+
+    fn descriptions(details: &[ChangeDetail]) -> Vec<(&str, bool)> {
+        details.iter().map(|detail| (detail.description.as_str(), detail.breaking)).collect()
+    }
+
+    #[test]
+    fn parameter_changes() {
+        let old = operation(json!({"parameters": [
+            {"name": "limit", "in": "query"},
+            {"name": "sort", "in": "query"},
+            {"name": "X-Trace", "in": "header"},
+        ]}));
+        let new = operation(json!({"parameters": [
+            {"name": "limit", "in": "query", "required": true},
+            {"name": "X-Trace", "in": "header"},
+            {"name": "tenant", "in": "header", "required": true},
+            {"name": "page", "in": "query"},
+        ]}));
+
+        let details = diff_operations(&old, &new);
+        let mut details = descriptions(&details);
+        details.sort();
+        assert_eq!(details, [
+            ("optional query parameter 'page' added", false),
+            ("query parameter 'limit' became required", true),
+            ("query parameter 'sort' removed", false),
+            ("required header parameter 'tenant' added", true),
+        ]);
+    }
+
+    #[test]
+    fn request_body_and_response_changes() {
+        let old = operation(json!({
+            "operationId": "createUser",
+            "requestBody": {"content": {"application/json": {}, "application/xml": {}}},
+            "responses": {"201": {}, "400": {}},
+        }));
+        let new = operation(json!({
+            "operationId": "addUser",
+            "deprecated": true,
+            "requestBody": {"required": true, "content": {"application/json": {}}},
+            "responses": {"200": {}},
+        }));
+
+        assert_eq!(descriptions(&diff_operations(&old, &new)), [
+            ("request body became required", true),
+            ("request media type 'application/xml' removed", true),
+            ("response 201 removed", true),
+            ("operationId changed from 'createUser' to 'addUser'", true),
+            ("operation deprecated", false),
+        ]);
+        assert!(diff_operations(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn renamed_parameters_are_changes() {
+        let old = spec(json!({"/users/{id}": {"get": {}}, "/orders": {"get": {}}}));
+        let new = spec(json!({"/users/{userId}": {"get": {}}, "/invoices": {"get": {}}}));
+
+        let changes = diff_specs(&old, &new);
+        assert_eq!(summary(&changes), [
+            (ChangeKind::Removed, "GET /orders".to_string()),
+            (ChangeKind::Changed, "GET /users/{id}".to_string()),
+            (ChangeKind::Added, "GET /invoices".to_string()),
+        ]);
+        assert!(!changes[1].is_breaking());
+    }
+
+    #[test]
+    fn templates_shared_on_one_method_stay_apart() {
+        let old = spec(json!({"/u/{id}": {"get": {}}, "/u/{name}": {"get": {"deprecated": true}}}));
+        let new = spec(json!({"/u/{id}": {"get": {}}}));
+
+        assert_eq!(summary(&diff_specs(&old, &new)), [(ChangeKind::Removed, "GET /u/{name}".to_string())]);
+        assert_eq!(summary(&diff_specs(&new, &old)), [(ChangeKind::Added, "GET /u/{name}".to_string())]);
+    }
+}
@@ -1,21 +1,37 @@
+pub mod cache;
+pub mod changes;
 pub mod cli;
+pub mod config;
+pub mod constants;
+pub mod detector;
+pub mod diff;
 pub mod openapi;
+pub mod paths;
+pub mod report;
+pub mod resolver;
+pub mod routes;
 pub mod scanner;
+pub mod sourcemap;
+pub mod syntax;
+pub mod template;
 pub mod analyzer;
 pub mod output;
+pub mod validation;
 
-pub use crate::cli::{CheckArgs, Cli};
+pub use crate::cli::{CheckArgs, Cli, DiffArgs, RoutesArgs, ValidateArgs};
 use crate::analyzer::EndpointAnalyzer;
 use crate::output::OutputFormatter;
 use anyhow::Result;
 
 /// Main entry point for the epcheck application
 pub async fn run(args: CheckArgs) -> Result<()> {
+    let args = config::apply(args)?;
+    if args.print_config {
+        return config::print_effective(&args);
+    }
+
     // Determine spec path
-    let spec_path = match &args.spec {
-        Some(s) => s.clone(),
-        None => cli::find_openapi_spec().ok_or_else(|| anyhow::anyhow!("No OpenAPI spec provided and none found in current or parent directories"))?,
-    };
+    let spec_path = cli::resolve_spec_path(&args.spec)?;
 
     // Load and parse OpenAPI specification
     let spec = cli::load_openapi_spec(&spec_path).await?;
@@ -23,12 +39,93 @@ pub async fn run(args: CheckArgs) -> Result<()> {
     // Create analyzer
     let analyzer = EndpointAnalyzer::new(spec, args.clone());
 
+    if let Some(rev) = &args.since {
+        let report = analyzer.analyze_changes(&args.dir, rev).await?;
+        return changes::print_changes(&report, &args);
+    }
+
     // Scan directory for endpoint usage
     let results = analyzer.analyze_directory(&args.dir).await?;
 
     // Format and output results
-    let formatter = OutputFormatter::new(args.format);
+    let deprecated_in_use = results.deprecated_in_use().len();
+    let unknown_calls = results.unknown_calls.len();
+    let formatter = OutputFormatter::new(args.output_format());
     formatter.output(results, &args)?;
 
+    if args.deprecation_audit && deprecated_in_use > 0 {
+        anyhow::bail!("Deprecation audit failed: {} deprecated endpoint(s) still in use", deprecated_in_use);
+    }
+    if unknown_calls > 0 {
+        anyhow::bail!("{} call(s) to endpoints not in the spec", unknown_calls);
+    }
+
+    Ok(())
+}
+
+/// Entry point for the validate command
+pub async fn run_validate(args: ValidateArgs) -> Result<()> {
+    let spec_path = cli::resolve_spec_path(&args.spec)?;
+    let source = cli::read_spec_source(&spec_path).await?;
+
+    let findings = validation::validate_spec(&source, &spec_path);
+    validation::print_findings(&findings, &spec_path, args.format)?;
+
+    let errors = findings.iter()
+        .filter(|f| f.severity == validation::Severity::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!("Validation failed: {} error(s) found", errors);
+    }
+
+    Ok(())
+}
+
+/// Entry point for the diff command
+pub async fn run_diff(mut args: DiffArgs) -> Result<()> {
+    args.check = config::apply(args.check)?;
+    let old_spec = cli::load_openapi_spec(&args.old).await?;
+    let new_spec = cli::load_openapi_spec(&args.new).await?;
+
+    let mut changes = diff::diff_specs(&old_spec, &new_spec);
+
+    // Scan code against the old spec to see which removed/changed endpoints are
+    // still referenced, without the result filters that would hide some of them
+    let scan_args = CheckArgs {
+        unused_only: false,
+        pattern: None,
+        tag: Vec::new(),
+        ..args.check.clone()
+    };
+    let analyzer = EndpointAnalyzer::new(old_spec, scan_args);
+    let usage = analyzer.analyze_directory(&args.check.dir).await?;
+    diff::attach_usage(&mut changes, &usage);
+
+    diff::print_diff(&changes, &args)?;
+
+    let breaking_in_use = changes.iter().filter(|c| c.is_breaking() && !c.files.is_empty()).count();
+    if breaking_in_use > 0 {
+        anyhow::bail!("{} breaking change(s) affect endpoints still used in code", breaking_in_use);
+    }
+
+    Ok(())
+}
+
+/// Entry point for the routes command
+pub async fn run_routes(mut args: RoutesArgs) -> Result<()> {
+    args.check = config::apply(args.check)?;
+    let spec_path = cli::resolve_spec_path(&args.check.spec)?;
+    let spec = cli::load_openapi_spec(&spec_path).await?;
+
+    let scanner = scanner::FileScanner::new(args.check.clone());
+    let routes = routes::extract_routes(&scanner, &args.check.dir, &routes::builtin_extractors())?;
+    let report = routes::compare_routes(&spec, routes);
+
+    routes::print_routes(&report, &args)?;
+
+    if !report.undocumented.is_empty() {
+        anyhow::bail!("{} route(s) are missing from the spec", report.undocumented.len());
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
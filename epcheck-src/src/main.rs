@@ -1,6 +1,6 @@
 // Main entry point for epcheck
 use clap::{CommandFactory, Parser};
-use epcheck::{run, Cli};
+use epcheck::{run, run_diff, run_routes, run_validate, Cli};
 use epcheck::cli::{Commands, Shell};
 use std::process;
 use std::path::PathBuf;
@@ -21,6 +21,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 process::exit(1);
             }
         }
+        Some(Commands::Validate(args)) => {
+            if let Err(e) = run_validate(args).await {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(Commands::Diff(args)) => {
+            if let Err(e) = run_diff(args).await {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(Commands::Routes(args)) => {
+            if let Err(e) = run_routes(args).await {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
         Some(Commands::Completions { shell, install }) => {
             let mut cmd = Cli::command();
             let shell_type = match shell {
@@ -69,7 +87,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
         Shell::Bash => {
             // Prefer user directory first
             let user_path = home_path.join(".bash_completion.d").join("epcheck");
-            if user_path.parent().map_or(false, |p| p.exists()) || std::fs::create_dir_all(user_path.parent().unwrap()).is_ok() {
+            if user_path.parent().is_some_and(|p| p.exists()) || std::fs::create_dir_all(user_path.parent().unwrap()).is_ok() {
                 return Ok(user_path);
             }
 
@@ -81,7 +99,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
 
             for path in system_paths {
                 if let Some(parent) = path.parent() {
-                    if parent.exists() && std::fs::metadata(parent).map_or(false, |m| !m.permissions().readonly()) {
+                    if parent.exists() && std::fs::metadata(parent).is_ok_and(|m| !m.permissions().readonly()) {
                         return Ok(path);
                     }
                 }
@@ -93,7 +111,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
         Shell::Zsh => {
             // Prefer user directory first
             let user_path = home_path.join(".zsh").join("completions").join("_epcheck");
-            if user_path.parent().map_or(false, |p| p.exists()) || std::fs::create_dir_all(user_path.parent().unwrap()).is_ok() {
+            if user_path.parent().is_some_and(|p| p.exists()) || std::fs::create_dir_all(user_path.parent().unwrap()).is_ok() {
                 return Ok(user_path);
             }
 
@@ -105,7 +123,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
 
             for path in system_paths {
                 if let Some(parent) = path.parent() {
-                    if parent.exists() && std::fs::metadata(parent).map_or(false, |m| !m.permissions().readonly()) {
+                    if parent.exists() && std::fs::metadata(parent).is_ok_and(|m| !m.permissions().readonly()) {
                         return Ok(path);
                     }
                 }
@@ -120,7 +138,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
                 .join("fish")
                 .join("completions");
             let path = fish_completions.join("epcheck.fish");
-            if path.parent().map_or(false, |p| p.exists()) || std::fs::create_dir_all(path.parent().unwrap()).is_ok() {
+            if path.parent().is_some_and(|p| p.exists()) || std::fs::create_dir_all(path.parent().unwrap()).is_ok() {
                 return Ok(path);
             }
             Ok(path)
@@ -132,7 +150,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
                 .and_then(|path| std::env::split_paths(&path).next())
                 .unwrap_or_else(|| home_path.join("Documents").join("WindowsPowerShell"));
             let path = ps_profile.join("epcheck.ps1");
-            if path.parent().map_or(false, |p| p.exists()) || std::fs::create_dir_all(path.parent().unwrap()).is_ok() {
+            if path.parent().is_some_and(|p| p.exists()) || std::fs::create_dir_all(path.parent().unwrap()).is_ok() {
                 return Ok(path);
             }
             Ok(path)
@@ -142,7 +160,7 @@ fn get_completion_path(shell: Shell) -> anyhow::Result<PathBuf> {
                 .join(".config")
                 .join("elvish")
                 .join("rc.elv");
-            if elvish_rc.parent().map_or(false, |p| p.exists()) || std::fs::create_dir_all(elvish_rc.parent().unwrap()).is_ok() {
+            if elvish_rc.parent().is_some_and(|p| p.exists()) || std::fs::create_dir_all(elvish_rc.parent().unwrap()).is_ok() {
                 return Ok(elvish_rc);
             }
             Ok(elvish_rc)
@@ -1,13 +1,72 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::resolver::escape_pointer_token;
 
-/// OpenAPI specification structure
+/// OpenAPI specification structure, normalized across Swagger 2.0, OpenAPI 3.0 and 3.1
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenApiSpec {
-    #[serde(rename = "openapi")]
+    #[serde(rename = "openapi", alias = "swagger")]
     pub version: Option<String>,
+    #[serde(skip)]
+    pub dialect: SpecDialect,
     pub info: Option<Info>,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    /// Swagger 2.0 base path prepended to every path
+    #[serde(rename = "basePath")]
+    pub base_path: Option<String>,
+    /// Swagger 2.0 host, folded into `servers` during normalization
+    pub host: Option<String>,
+    /// Swagger 2.0 transfer protocols, folded into `servers` during normalization
+    #[serde(default)]
+    pub schemes: Vec<String>,
+    /// Swagger 2.0 default request media types
+    #[serde(default)]
+    pub consumes: Vec<String>,
+    /// Swagger 2.0 default response media types
+    #[serde(default)]
+    pub produces: Vec<String>,
+    #[serde(default)]
     pub paths: HashMap<String, PathItem>,
+    /// OpenAPI 3.1 webhooks (requests the API sends, so never matched against client code)
+    #[serde(default)]
+    pub webhooks: HashMap<String, PathItem>,
+}
+
+/// Specification dialect detected when loading a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpecDialect {
+    Swagger2,
+    #[default]
+    OpenApi30,
+    OpenApi31,
+}
+
+impl SpecDialect {
+    /// Detect the dialect from the `swagger`/`openapi` version field
+    pub fn detect(document: &serde_json::Value) -> anyhow::Result<Self> {
+        if let Some(version) = document.get("swagger") {
+            return match version.as_str() {
+                Some("2.0") => Ok(Self::Swagger2),
+                _ => Err(anyhow::anyhow!("Unsupported Swagger version: {}", version)),
+            };
+        }
+
+        match document.get("openapi") {
+            Some(serde_json::Value::String(version)) if version.starts_with("3.0") => Ok(Self::OpenApi30),
+            Some(serde_json::Value::String(version)) if version.starts_with("3.1") => Ok(Self::OpenApi31),
+            Some(version) => Err(anyhow::anyhow!("Unsupported OpenAPI version: {}", version)),
+            None => Err(anyhow::anyhow!("Not an OpenAPI document: missing 'openapi' or 'swagger' version field")),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Swagger2 => "Swagger 2.0",
+            Self::OpenApi30 => "OpenAPI 3.0",
+            Self::OpenApi31 => "OpenAPI 3.1",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,23 +75,437 @@ pub struct Info {
     pub version: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Server {
+    pub url: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, ServerVariable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerVariable {
+    #[serde(rename = "enum", default)]
+    pub values: Vec<String>,
+    pub default: String,
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PathItem {
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    /// Parameters shared by every operation on this path
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
     #[serde(flatten)]
     pub operations: HashMap<String, Operation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Operation {
     pub summary: Option<String>,
     pub description: Option<String>,
     pub operation_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Operation parameters, including those inherited from the path item
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+    pub request_body: Option<RequestBody>,
+    #[serde(default)]
+    pub responses: HashMap<String, Response>,
+    /// Security requirements; `None` inherits the spec default, an empty list disables security
+    pub security: Option<Vec<HashMap<String, Vec<String>>>>,
+    #[serde(default)]
+    pub deprecated: bool,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    /// Swagger 2.0 request media types
+    #[serde(default)]
+    pub consumes: Vec<String>,
+    /// Swagger 2.0 response media types
+    #[serde(default)]
+    pub produces: Vec<String>,
+    /// Specification extensions (`x-` keys)
     #[serde(flatten)]
-    pub parameters: HashMap<String, serde_json::Value>,
+    pub extensions: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: ParameterLocation,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub deprecated: bool,
+    pub schema: Option<serde_json::Value>,
+    /// Swagger 2.0 non-body parameter type
+    #[serde(rename = "type")]
+    pub param_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+    /// Swagger 2.0 only, normalized into `Operation::request_body`
+    Body,
+    /// Swagger 2.0 only, normalized into `Operation::request_body`
+    FormData,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestBody {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub content: HashMap<String, MediaType>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaType {
+    pub schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub content: HashMap<String, MediaType>,
+    /// Swagger 2.0 response schema, normalized into `content`
+    #[serde(skip_serializing)]
+    pub schema: Option<serde_json::Value>,
+}
+
+/// Upper bound on server URL expansions to guard against huge variable enums
+const MAX_SERVER_EXPANSIONS: usize = 64;
+
+impl OpenApiSpec {
+    /// Build a spec from a resolved document, normalizing the detected dialect.
+    ///
+    /// Unsupported versions are rejected rather than parsed on a best-effort basis.
+    pub fn from_document(mut document: serde_json::Value) -> anyhow::Result<Self> {
+        let dialect = SpecDialect::detect(&document)?;
+        strip_path_item_extensions(&mut document);
+        for (pointer, reference) in strip_unresolved_refs(&mut document) {
+            tracing::warn!("Skipping #{}: reference '{}' was not resolved", pointer, reference);
+        }
+
+        let mut spec: OpenApiSpec = serde_json::from_value(document)?;
+        spec.dialect = dialect;
+
+        if dialect == SpecDialect::Swagger2 {
+            spec.normalize_swagger2();
+        }
+        spec.normalize_operations();
+
+        Ok(spec)
+    }
+
+    /// Inherit path-level parameters and keep only `x-` keys as extensions
+    fn normalize_operations(&mut self) {
+        for path_item in self.paths.values_mut().chain(self.webhooks.values_mut()) {
+            for (method, operation) in path_item.operations.iter_mut() {
+                if HttpMethod::from_str(method).is_none() {
+                    continue;
+                }
+
+                // Operation-level parameters override path-level ones with the same name and location
+                for inherited in &path_item.parameters {
+                    let overridden = operation.parameters.iter()
+                        .any(|p| p.name == inherited.name && p.location == inherited.location);
+                    if !overridden {
+                        operation.parameters.push(inherited.clone());
+                    }
+                }
+
+                operation.extensions.retain(|key, _| key.starts_with("x-"));
+            }
+        }
+    }
+
+    /// Fold Swagger 2.0 constructs into their OpenAPI 3 equivalents
+    fn normalize_swagger2(&mut self) {
+        if self.servers.is_empty() {
+            if let Some(server) = self.swagger2_server() {
+                self.servers.push(server);
+            }
+        }
+
+        let consumes = self.consumes.clone();
+        let produces = self.produces.clone();
+        for path_item in self.paths.values_mut() {
+            for operation in path_item.operations.values_mut() {
+                operation.normalize_swagger2(&consumes, &produces);
+            }
+        }
+    }
+
+    /// Build a server from Swagger 2.0 `schemes`, `host` and `basePath`
+    fn swagger2_server(&self) -> Option<Server> {
+        let base_path = self.base_path.clone().unwrap_or_default();
+        let url = match &self.host {
+            Some(host) => {
+                let scheme = self.schemes.first().map(String::as_str).unwrap_or("https");
+                format!("{}://{}{}", scheme, host, base_path)
+            }
+            None => base_path,
+        };
+
+        if url.is_empty() {
+            return None;
+        }
+
+        Some(Server {
+            url,
+            description: None,
+            variables: HashMap::new(),
+        })
+    }
+
+    /// Iterate over every operation in `paths` together with its endpoint
+    pub fn operations(&self) -> impl Iterator<Item = (Endpoint, &Operation)> + '_ {
+        self.paths.iter().flat_map(|(path, path_item)| {
+            path_item.operations.iter().filter_map(move |(method_str, operation)| {
+                HttpMethod::from_str(method_str)
+                    .map(|method| (Endpoint::new(path.clone(), method), operation))
+            })
+        })
+    }
+
+    /// Look up the operation behind an endpoint
+    pub fn operation(&self, endpoint: &Endpoint) -> Option<&Operation> {
+        let path_item = self.paths.get(&endpoint.path)?;
+        path_item.operations.iter()
+            .find(|(method_str, _)| HttpMethod::from_str(method_str) == Some(endpoint.method))
+            .map(|(_, operation)| operation)
+    }
+
+    /// Path prefixes that clients may put in front of every spec path.
+    ///
+    /// Collected from the path portion of every `servers` URL (top-level and
+    /// path-level, including a normalized Swagger 2.0 `basePath`), with server
+    /// variables expanded from their enum values or defaults.
+    pub fn base_paths(&self) -> Vec<String> {
+        let mut prefixes = Vec::new();
+
+        let servers = self.servers.iter()
+            .chain(self.paths.values().flat_map(|item| item.servers.iter()));
+        for server in servers {
+            for url in server.expand_url() {
+                prefixes.push(url_path(&url).to_string());
+            }
+        }
+
+        let mut base_paths: Vec<String> = prefixes.into_iter()
+            .map(|prefix| prefix.trim_end_matches('/').to_string())
+            .filter(|prefix| !prefix.is_empty())
+            .collect();
+        base_paths.sort();
+        base_paths.dedup();
+        base_paths
+    }
+}
+
+impl Operation {
+    /// Move Swagger 2.0 body/formData parameters and response schemas into OpenAPI 3 form
+    fn normalize_swagger2(&mut self, default_consumes: &[String], default_produces: &[String]) {
+        let consumes = media_types(&self.consumes, default_consumes);
+        let produces = media_types(&self.produces, default_produces);
+
+        let (body_params, params): (Vec<Parameter>, Vec<Parameter>) = std::mem::take(&mut self.parameters)
+            .into_iter()
+            .partition(|p| matches!(p.location, ParameterLocation::Body | ParameterLocation::FormData));
+        self.parameters = params;
+
+        if let Some(body) = body_params.iter().find(|p| p.location == ParameterLocation::Body) {
+            self.request_body = Some(RequestBody {
+                description: body.description.clone(),
+                content: consumes.iter()
+                    .map(|media| (media.clone(), MediaType { schema: body.schema.clone() }))
+                    .collect(),
+                required: body.required,
+            });
+        } else if !body_params.is_empty() {
+            // formData parameters become the properties of an object schema
+            let properties: serde_json::Map<String, serde_json::Value> = body_params.iter()
+                .map(|p| (p.name.clone(), serde_json::json!({ "type": p.param_type.as_deref().unwrap_or("string") })))
+                .collect();
+            let required: Vec<&str> = body_params.iter()
+                .filter(|p| p.required)
+                .map(|p| p.name.as_str())
+                .collect();
+            let schema = serde_json::json!({ "type": "object", "properties": properties, "required": required });
+            let media = if body_params.iter().any(|p| p.param_type.as_deref() == Some("file")) {
+                "multipart/form-data"
+            } else {
+                "application/x-www-form-urlencoded"
+            };
+
+            self.request_body = Some(RequestBody {
+                description: None,
+                content: HashMap::from([(media.to_string(), MediaType { schema: Some(schema) })]),
+                required: !required.is_empty(),
+            });
+        }
+
+        for response in self.responses.values_mut() {
+            if let Some(schema) = response.schema.take() {
+                response.content = produces.iter()
+                    .map(|media| (media.clone(), MediaType { schema: Some(schema.clone()) }))
+                    .collect();
+            }
+        }
+    }
+}
+
+/// Operation-level media types, falling back to spec defaults and then JSON
+fn media_types(own: &[String], defaults: &[String]) -> Vec<String> {
+    if !own.is_empty() {
+        own.to_vec()
+    } else if !defaults.is_empty() {
+        defaults.to_vec()
+    } else {
+        vec!["application/json".to_string()]
+    }
+}
+
+/// Drop `x-` keys from path items so the flattened operation map only sees operations
+fn strip_path_item_extensions(document: &mut serde_json::Value) {
+    for section in ["paths", "webhooks"] {
+        if let Some(serde_json::Value::Object(path_items)) = document.get_mut(section) {
+            for path_item in path_items.values_mut() {
+                if let serde_json::Value::Object(fields) = path_item {
+                    fields.retain(|key, _| !key.starts_with("x-"));
+                }
+            }
+        }
+    }
+}
+
+/// Remove path items and parameters that are still `$ref` objects, which the
+/// resolver leaves in place for remote and cyclic references, returning the
+/// JSON pointer and reference of each
+pub fn strip_unresolved_refs(document: &mut serde_json::Value) -> Vec<(String, String)> {
+    let mut skipped = Vec::new();
+
+    for section in ["paths", "webhooks"] {
+        let path_items = match document.get_mut(section) {
+            Some(serde_json::Value::Object(path_items)) => path_items,
+            _ => continue,
+        };
+
+        path_items.retain(|path, path_item| {
+            let pointer = format!("/{}/{}", section, escape_pointer_token(path));
+            match unresolved_ref(path_item) {
+                Some(reference) => {
+                    skipped.push((pointer, reference));
+                    false
+                }
+                None => {
+                    strip_unresolved_parameters(path_item, &pointer, &mut skipped);
+                    true
+                }
+            }
+        });
+    }
+
+    skipped
+}
+
+fn strip_unresolved_parameters(path_item: &mut serde_json::Value, pointer: &str, skipped: &mut Vec<(String, String)>) {
+    let fields = match path_item.as_object_mut() {
+        Some(fields) => fields,
+        None => return,
+    };
+
+    for (key, field) in fields.iter_mut() {
+        let parameters = if key == "parameters" {
+            Some(field)
+        } else if HttpMethod::from_str(key).is_some() {
+            field.get_mut("parameters")
+        } else {
+            None
+        };
+
+        if let Some(serde_json::Value::Array(parameters)) = parameters {
+            let prefix = if key == "parameters" {
+                format!("{}/parameters", pointer)
+            } else {
+                format!("{}/{}/parameters", pointer, escape_pointer_token(key))
+            };
+            let mut index = 0;
+            parameters.retain(|parameter| {
+                let unresolved = unresolved_ref(parameter);
+                if let Some(reference) = &unresolved {
+                    skipped.push((format!("{}/{}", prefix, index), reference.clone()));
+                }
+                index += 1;
+                unresolved.is_none()
+            });
+        }
+    }
+}
+
+fn unresolved_ref(value: &serde_json::Value) -> Option<String> {
+    value.get("$ref").and_then(serde_json::Value::as_str).map(str::to_string)
+}
+
+impl Server {
+    /// Expand `{variable}` placeholders in the server URL into every concrete URL
+    pub fn expand_url(&self) -> Vec<String> {
+        let mut urls = vec![self.url.clone()];
+
+        for (name, variable) in &self.variables {
+            let placeholder = format!("{{{}}}", name);
+            let placeholder = placeholder.as_str();
+            if !self.url.contains(placeholder) {
+                continue;
+            }
+
+            let values: Vec<&String> = if variable.values.is_empty() {
+                vec![&variable.default]
+            } else {
+                variable.values.iter().collect()
+            };
+
+            urls = urls.iter()
+                .flat_map(|url| values.iter().map(move |value| url.replace(placeholder, value)))
+                .take(MAX_SERVER_EXPANSIONS)
+                .collect();
+        }
+
+        urls
+    }
+}
+
+/// Extract the path portion of a server URL (`https://x/api/v2` -> `/api/v2`)
+fn url_path(url: &str) -> &str {
+    let without_scheme = match url.find("://") {
+        Some(index) => &url[index + 3..],
+        None if url.starts_with("//") => &url[2..],
+        None => return url.split(['?', '#']).next().unwrap_or(""),
+    };
+
+    match without_scheme.find('/') {
+        Some(index) => without_scheme[index..].split(['?', '#']).next().unwrap_or(""),
+        None => "",
+    }
 }
 
 /// HTTP methods supported by OpenAPI
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HttpMethod {
     Get,
     Post,
@@ -45,6 +518,8 @@ pub enum HttpMethod {
 }
 
 impl HttpMethod {
+    /// Case-insensitive method name; `None` for anything else
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "get" => Some(Self::Get),
@@ -74,7 +549,7 @@ impl HttpMethod {
 }
 
 /// Endpoint definition combining path and method
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Endpoint {
     pub path: String,
     pub method: HttpMethod,
@@ -94,15 +569,182 @@ impl std::fmt::Display for Endpoint {
 
 /// Extract all endpoints from an OpenAPI specification
 pub fn extract_endpoints(spec: &OpenApiSpec) -> Vec<Endpoint> {
-    let mut endpoints = Vec::new();
+    spec.operations().map(|(endpoint, _operation)| endpoint).collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    for (path, path_item) in &spec.paths {
-        for (method_str, _operation) in &path_item.operations {
-            if let Some(method) = HttpMethod::from_str(method_str) {
-                endpoints.push(Endpoint::new(path.clone(), method));
-            }
+    fn spec(document: serde_json::Value) -> OpenApiSpec {
+        OpenApiSpec::from_document(document).unwrap()
+    }
+
+    fn server(url: &str, variables: serde_json::Value) -> Server {
+        serde_json::from_value(json!({"url": url, "variables": variables})).unwrap()
+    }
+
+    #[test]
+    fn server_variables_expand_from_enum_or_default() {
+        let server = server("https://{env}.example.com/{version}", json!({
+            "env": {"enum": ["api", "staging"], "default": "api"},
+            "version": {"default": "v1"},
+        }));
+
+        let mut urls = server.expand_url();
+        urls.sort();
+        assert_eq!(urls, ["https://api.example.com/v1", "https://staging.example.com/v1"]);
+    }
+
+    #[test]
+    fn base_paths_come_from_server_urls() {
+        let spec = spec(json!({
+            "openapi": "3.0.3",
+            "servers": [
+                {"url": "/api/v2/"},
+                {"url": "https://example.com/api/v2"},
+                {"url": "//cdn.example.com/static?v=1"},
+                {"url": "https://example.com"},
+            ],
+            "paths": {"/users": {"servers": [{"url": "https://users.example.com/{base}", "variables": {"base": {"default": "people"}}}]}},
+        }));
+
+        assert_eq!(spec.base_paths(), ["/api/v2", "/people", "/static"]);
+    }
+
+    #[test]
+    fn swagger2_base_path_becomes_a_server() {
+        let hosted = spec(json!({
+            "swagger": "2.0",
+            "host": "api.example.com",
+            "basePath": "/v1",
+            "schemes": ["http"],
+            "paths": {},
+        }));
+        assert_eq!(hosted.servers[0].url, "http://api.example.com/v1");
+        assert_eq!(hosted.base_paths(), ["/v1"]);
+
+        // Without a host the base path alone is a relative server URL
+        let relative = spec(json!({"swagger": "2.0", "basePath": "/v1", "paths": {}}));
+        assert_eq!(relative.servers[0].url, "/v1");
+        assert_eq!(relative.base_paths(), ["/v1"]);
+    }
+    #[test]
+    fn dialects_are_detected() {
+        assert_eq!(SpecDialect::detect(&json!({"swagger": "2.0"})).unwrap(), SpecDialect::Swagger2);
+        assert_eq!(SpecDialect::detect(&json!({"openapi": "3.0.3"})).unwrap(), SpecDialect::OpenApi30);
+        assert_eq!(SpecDialect::detect(&json!({"openapi": "3.1.0"})).unwrap(), SpecDialect::OpenApi31);
+
+        for document in [json!({"swagger": "1.2"}), json!({"openapi": "4.0.0"}), json!({"openapi": 3.1}), json!({"info": {}})] {
+            assert!(SpecDialect::detect(&document).is_err(), "{} was accepted", document);
         }
     }
 
-    endpoints
-}
\ No newline at end of file
+    #[test]
+    fn openapi30_paths_inherit_parameters() {
+        let spec = spec(json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/users/{id}": {
+                    "x-owner": "accounts",
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true},
+                        {"name": "expand", "in": "query"},
+                    ],
+                    "get": {
+                        "operationId": "getUser",
+                        "parameters": [{"name": "expand", "in": "query", "required": true}],
+                        "x-internal": true,
+                    },
+                },
+            },
+        }));
+
+        assert_eq!(spec.dialect, SpecDialect::OpenApi30);
+        let get = &spec.paths["/users/{id}"].operations["get"];
+        assert_eq!(get.operation_id.as_deref(), Some("getUser"));
+        // The operation's own `expand` overrides the path-level one
+        let names: Vec<(&str, bool)> = get.parameters.iter().map(|p| (p.name.as_str(), p.required)).collect();
+        assert_eq!(names, [("expand", true), ("id", true)]);
+        assert_eq!(get.extensions.keys().collect::<Vec<_>>(), ["x-internal"]);
+        assert_eq!(extract_endpoints(&spec), [Endpoint::new("/users/{id}".to_string(), HttpMethod::Get)]);
+    }
+
+    #[test]
+    fn openapi31_webhooks_are_not_endpoints() {
+        let spec = spec(json!({
+            "openapi": "3.1.0",
+            "paths": {"/users": {"post": {"requestBody": {"content": {"application/json": {}}}}}},
+            "webhooks": {"userCreated": {"post": {"operationId": "onUserCreated"}}},
+        }));
+
+        assert_eq!(spec.dialect, SpecDialect::OpenApi31);
+        assert!(spec.webhooks["userCreated"].operations.contains_key("post"));
+        assert_eq!(extract_endpoints(&spec), [Endpoint::new("/users".to_string(), HttpMethod::Post)]);
+    }
+
+    #[test]
+    fn swagger2_body_parameters_become_a_request_body() {
+        let spec = spec(json!({
+            "swagger": "2.0",
+            "consumes": ["application/xml"],
+            "paths": {
+                "/users": {
+                    "post": {
+                        "parameters": [
+                            {"name": "user", "in": "body", "required": true, "schema": {"type": "object"}},
+                            {"name": "dryRun", "in": "query", "type": "boolean"},
+                        ],
+                        "responses": {"201": {"description": "created", "schema": {"type": "object"}}},
+                    },
+                },
+            },
+        }));
+
+        let post = &spec.paths["/users"].operations["post"];
+        let body = post.request_body.as_ref().unwrap();
+        assert!(body.required);
+        assert_eq!(body.content["application/xml"].schema, Some(json!({"type": "object"})));
+        assert_eq!(post.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["dryRun"]);
+        // Responses fall back to JSON without `produces`
+        assert_eq!(post.responses["201"].content["application/json"].schema, Some(json!({"type": "object"})));
+    }
+
+    #[test]
+    fn swagger2_form_data_becomes_an_object_schema() {
+        let spec = spec(json!({
+            "swagger": "2.0",
+            "paths": {
+                "/avatars": {
+                    "post": {
+                        "parameters": [
+                            {"name": "file", "in": "formData", "type": "file", "required": true},
+                            {"name": "caption", "in": "formData", "type": "string"},
+                        ],
+                    },
+                },
+            },
+        }));
+
+        let body = spec.paths["/avatars"].operations["post"].request_body.as_ref().unwrap();
+        assert!(body.required);
+        let schema = body.content["multipart/form-data"].schema.as_ref().unwrap();
+        assert_eq!(schema["properties"]["file"], json!({"type": "file"}));
+        assert_eq!(schema["properties"]["caption"], json!({"type": "string"}));
+        assert_eq!(schema["required"], json!(["file"]));
+    }
+
+    #[test]
+    fn unresolved_refs_are_skipped() {
+        let spec = spec(json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/remote": {"$ref": "https://example.com/paths.yaml#/users"},
+                "/users": {"get": {"parameters": [{"$ref": "https://example.com/params.yaml#/limit"}]}},
+            },
+        }));
+
+        assert_eq!(spec.paths.keys().collect::<Vec<_>>(), ["/users"]);
+        assert!(spec.paths["/users"].operations["get"].parameters.is_empty());
+    }
+}
@@ -1,5 +1,8 @@
-use crate::analyzer::{AnalysisResult, EndpointStatus};
-use crate::cli::{CheckArgs, OutputFormat};
+use crate::analyzer::{AnalysisResult, EndpointResult, EndpointStatus};
+use crate::cli::{CheckArgs, GroupBy, OutputFormat};
+use crate::report::{grouped, print_markdown_header};
+use crate::scanner::{MatchKind, MatchLocation};
+use std::collections::BTreeMap;
 
 /// Output formatter for analysis results
 pub struct OutputFormatter {
@@ -26,6 +29,10 @@ impl OutputFormatter {
         println!("OpenAPI Endpoint Usage Report");
         println!("Generated on {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
         println!("API Spec: {}", cli.spec.as_deref().unwrap_or("auto-detected"));
+        println!("Spec Dialect: {}", results.dialect.as_str());
+        if results.webhook_count > 0 {
+            println!("Webhooks: {} (not checked for usage)", results.webhook_count);
+        }
         println!("Search Dir: {}", cli.dir.display());
 
         // Show exclusions if any
@@ -48,21 +55,103 @@ impl OutputFormatter {
             println!("Filter: Unused endpoints only");
         }
 
+        if !cli.tag.is_empty() {
+            println!("Filter: Tags {}", cli.tag.join(", "));
+        }
+
         println!("{}", "=".repeat(80));
 
+        if cli.group_by == Some(GroupBy::Tag) {
+            for (tag, endpoints) in group_by_tag(&results.endpoints) {
+                let (used, total) = coverage(&endpoints);
+                println!("\n[{}] {}/{} used ({:.1}% coverage)", tag, used, total, percentage(used, total));
+                self.print_table_rows(&endpoints, cli);
+            }
+        } else {
+            let endpoints: Vec<&EndpointResult> = results.endpoints.iter().collect();
+            self.print_table_rows(&endpoints, cli);
+        }
+
+        let all_endpoints: Vec<&EndpointResult> = results.endpoints.iter().collect();
+        let (used_count, total_count) = coverage(&all_endpoints);
+        let total_file_refs: usize = results.endpoints.iter().map(|r| r.usage_count).sum();
+
+        println!("\nSummary:");
+        println!("  Total endpoints: {}", total_count);
+        println!("  Used: {}", used_count);
+        println!("  Unused: {}", total_count - used_count);
+        if total_count > 0 {
+            println!("  Coverage: {:.1}%", percentage(used_count, total_count));
+        }
+        println!("  Total file references: {}", total_file_refs);
+        let deprecated_in_use = results.deprecated_in_use();
+        if !deprecated_in_use.is_empty() {
+            println!("  Deprecated but still used: {}", deprecated_in_use.len());
+        }
+        if cli.unknown {
+            println!("  Calls to unknown endpoints: {}", results.unknown_calls.len());
+        }
+
+        // Detailed file references section
+        let multi_usage_endpoints: Vec<_> = results.endpoints.iter()
+            .filter(|r| r.usage_count >= 2)
+            .collect();
+
+        if !multi_usage_endpoints.is_empty() {
+            println!("\nDetailed File References (for endpoints with 2+ usages):");
+            for result in multi_usage_endpoints {
+                println!("  {} {}: {} files",
+                         result.endpoint.method.as_str(),
+                         result.endpoint.path,
+                         result.usage_count);
+                for file in &result.files {
+                    println!("    - {}", file_label(result, file));
+                }
+            }
+        } else {
+            let message = if cli.unused_only {
+                "No unused endpoints have multiple file references."
+            } else {
+                "No endpoints with 2 or more file references found."
+            };
+            println!("\nDetailed File References (for endpoints with 2+ usages):");
+            println!("  {}", message);
+        }
+
+        if cli.verbose {
+            print_locations(&results.endpoints);
+        }
+
+        if cli.deprecation_audit {
+            print_deprecation_audit(&results, "\n", "  ");
+        }
+
+        if cli.unknown {
+            print_unknown_calls(&results, "\n", "  ");
+        }
+
+        println!("\nNote: This script searches for endpoint usage in multiple patterns:");
+        println!("      1. Exact string matches: \"\"");
+        println!("      2. Method calls: .GET(\"\"), .POST(\"\"), etc.");
+        println!("      3. Path parameters: {{id}} matches actual values like 123, abc, etc.");
+        println!("      4. Generated client methods named after the operationId (marked \"via operationId\")");
+        println!("      The API spec file is automatically excluded from the search results.");
+
+        Ok(())
+    }
+
+    /// Print the endpoint table (header and one row per endpoint)
+    fn print_table_rows(&self, endpoints: &[&EndpointResult], cli: &CheckArgs) {
         // Calculate dynamic column widths
         let mut max_endpoint_len = 7; // "Endpoint" header
         let mut max_method_len = 7;  // "Methods" header
         let mut max_status_len = 6;  // "Status" header
         let mut max_count_len = 5;   // "Count" header
 
-        for result in &results.endpoints {
+        for result in endpoints {
             max_endpoint_len = max_endpoint_len.max(result.endpoint.path.len());
             max_method_len = max_method_len.max(result.endpoint.method.as_str().len());
-            max_status_len = max_status_len.max(match result.status {
-                EndpointStatus::Used => 6, // "✓ USED"
-                EndpointStatus::Unused => 8, // "✗ UNUSED"
-            });
+            max_status_len = max_status_len.max(status_label(result).chars().count());
             max_count_len = max_count_len.max(result.usage_count.to_string().len());
         }
 
@@ -81,22 +170,8 @@ impl OutputFormatter {
                  count_width = max_count_len);
         println!("{}", "-".repeat(max_endpoint_len + max_method_len + max_status_len + max_count_len + 6)); // +6 for spaces between columns
 
-        let mut used_count = 0;
-        let mut total_count = 0;
-        let mut total_file_refs = 0;
-
-        for result in &results.endpoints {
-            total_count += 1;
-            total_file_refs += result.usage_count;
-
-            if result.status == EndpointStatus::Used {
-                used_count += 1;
-            }
-
-            let status = match result.status {
-                EndpointStatus::Used => "✓ USED",
-                EndpointStatus::Unused => "✗ UNUSED",
-            };
+        for result in endpoints {
+            let status = status_label(result);
 
             let files_str = if result.files.is_empty() {
                 "-".to_string()
@@ -104,9 +179,7 @@ impl OutputFormatter {
                 format!("{} files (truncated)", result.files.len())
             } else {
                 result.files.iter()
-                    .map(|f| std::path::Path::new(f).file_name()
-                         .and_then(|n| n.to_str())
-                         .unwrap_or(f))
+                    .map(|f| file_label(result, f))
                     .collect::<Vec<_>>()
                     .join(", ")
             };
@@ -122,56 +195,10 @@ impl OutputFormatter {
                      status_width = max_status_len,
                      count_width = max_count_len);
         }
-
-        println!("\nSummary:");
-        println!("  Total endpoints: {}", total_count);
-        println!("  Used: {}", used_count);
-        println!("  Unused: {}", total_count - used_count);
-        if total_count > 0 {
-            println!("  Coverage: {:.1}%", (used_count as f64 / total_count as f64) * 100.0);
-        }
-        println!("  Total file references: {}", total_file_refs);
-
-        // Detailed file references section
-        let multi_usage_endpoints: Vec<_> = results.endpoints.iter()
-            .filter(|r| r.usage_count >= 2)
-            .collect();
-
-        if !multi_usage_endpoints.is_empty() {
-            println!("\nDetailed File References (for endpoints with 2+ usages):");
-            for result in multi_usage_endpoints {
-                println!("  {} {}: {} files",
-                         result.endpoint.method.as_str(),
-                         result.endpoint.path,
-                         result.usage_count);
-                for file in &result.files {
-                    let filename = std::path::Path::new(file).file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or(file);
-                    println!("    - {}", filename);
-                }
-            }
-        } else {
-            let message = if cli.unused_only {
-                "No unused endpoints have multiple file references."
-            } else {
-                "No endpoints with 2 or more file references found."
-            };
-            println!("\nDetailed File References (for endpoints with 2+ usages):");
-            println!("  {}", message);
-        }
-
-        println!("\nNote: This script searches for endpoint usage in multiple patterns:");
-        println!("      1. Exact string matches: \"{}\"", "");
-        println!("      2. Method calls: .GET(\"\"), .POST(\"\"), etc.");
-        println!("      3. Path parameters: {{id}} matches actual values like 123, abc, etc.");
-        println!("      The API spec file is automatically excluded from the search results.");
-
-        Ok(())
     }
 
     fn output_csv(&self, results: AnalysisResult) -> anyhow::Result<()> {
-        println!("Endpoint,Method,Status,Usage Count,Files");
+        println!("Endpoint,Method,Status,Usage Count,Files,Via OperationId,Tags,Deprecated");
 
         for result in &results.endpoints {
             let status = match result.status {
@@ -180,13 +207,26 @@ impl OutputFormatter {
             };
 
             let files = result.files.join(";");
+            let via_operation_id = result.operation_id_files.join(";");
+            let tags = result.tags.join(";");
 
-            println!("\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"",
+            println!("\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"",
                      result.endpoint.path,
                      result.endpoint.method.as_str(),
                      status,
                      result.usage_count,
-                     files);
+                     files,
+                     via_operation_id,
+                     tags,
+                     result.deprecated);
+        }
+
+        // Unknown calls are one row each, located by `file:line:column`
+        for call in &results.unknown_calls {
+            println!("\"{}\",\"{}\",\"UNKNOWN\",\"1\",\"{}\",\"\",\"\",\"false\"",
+                     call.url.replace('"', "\"\""),
+                     call.method.as_str(),
+                     location_label(&call.location));
         }
 
         Ok(())
@@ -208,15 +248,28 @@ impl OutputFormatter {
                     "method": result.endpoint.method.as_str(),
                     "status": status,
                     "usage_count": result.usage_count,
-                    "files": result.files
+                    "files": result.files,
+                    "via_operation_id": result.operation_id_files,
+                    "tags": result.tags,
+                    "deprecated": result.deprecated,
+                    "locations": result.locations.iter().map(|location| json!({
+                        "file": location.file,
+                        "line": location.line,
+                        "column": location.column,
+                        "snippet": location.snippet,
+                        "pattern": location.pattern,
+                        "via_operation_id": location.kind == MatchKind::OperationId
+                    })).collect::<Vec<_>>()
                 })
             })
             .collect();
 
-        let output = json!({
+        let mut output = json!({
             "report": {
                 "generated": chrono::Utc::now().to_rfc3339(),
                 "api_spec": cli.spec,
+                "spec_dialect": results.dialect.as_str(),
+                "webhooks": results.webhook_count,
                 "search_dir": cli.dir.to_string_lossy(),
                 "files_scanned": results.total_files_scanned,
                 "scan_time_ms": results.scan_time_ms
@@ -224,30 +277,107 @@ impl OutputFormatter {
             "endpoints": endpoints
         });
 
+        if cli.deprecation_audit {
+            let deprecated_in_use: Vec<serde_json::Value> = results.deprecated_in_use()
+                .into_iter()
+                .map(|result| json!({
+                    "endpoint": result.endpoint.path,
+                    "method": result.endpoint.method.as_str(),
+                    "files": result.files
+                }))
+                .collect();
+            output["deprecation_audit"] = json!({
+                "passed": deprecated_in_use.is_empty(),
+                "deprecated_in_use": deprecated_in_use
+            });
+        }
+
+        if cli.unknown {
+            let unknown: Vec<serde_json::Value> = results.unknown_calls.iter()
+                .map(|call| json!({
+                    "url": call.url,
+                    "method": call.method.as_str(),
+                    "host": call.host,
+                    "file": call.location.file,
+                    "line": call.location.line,
+                    "column": call.location.column,
+                    "snippet": call.location.snippet,
+                    "pattern": call.location.pattern
+                }))
+                .collect();
+            output["unknown_endpoints"] = json!(unknown);
+        }
+
+        if cli.group_by == Some(GroupBy::Tag) {
+            let groups: Vec<serde_json::Value> = group_by_tag(&results.endpoints)
+                .into_iter()
+                .map(|(tag, endpoints)| {
+                    let (used, total) = coverage(&endpoints);
+                    json!({
+                        "tag": tag,
+                        "total": total,
+                        "used": used,
+                        "unused": total - used,
+                        "coverage": percentage(used, total)
+                    })
+                })
+                .collect();
+            output["groups"] = json!(groups);
+        }
+
         println!("{}", serde_json::to_string_pretty(&output)?);
         Ok(())
     }
 
     fn output_markdown(&self, results: AnalysisResult, cli: &CheckArgs) -> anyhow::Result<()> {
-        // Print table header
-        println!("| Endpoint | Methods | Status | Count | Files |");
-        println!("|----------|---------|--------|-------|-------|");
+        println!("Spec dialect: {}\n", results.dialect.as_str());
+
+        if cli.group_by == Some(GroupBy::Tag) {
+            for (tag, endpoints) in group_by_tag(&results.endpoints) {
+                let (used, total) = coverage(&endpoints);
+                println!("### {} ({}/{} used, {:.1}% coverage)\n", tag, used, total, percentage(used, total));
+                self.print_markdown_rows(&endpoints, cli);
+                println!();
+            }
+        } else {
+            let endpoints: Vec<&EndpointResult> = results.endpoints.iter().collect();
+            self.print_markdown_rows(&endpoints, cli);
+        }
 
-        for result in &results.endpoints {
-            let status = match result.status {
-                EndpointStatus::Used => "✓ USED",
-                EndpointStatus::Unused => "✗ UNUSED",
+        if cli.deprecation_audit {
+            print_deprecation_audit(&results, "\n### ", "- ");
+        }
+
+        if cli.unknown {
+            print_unknown_calls(&results, "\n### ", "- ");
+        }
+
+        Ok(())
+    }
+
+    /// Print a markdown endpoint table
+    fn print_markdown_rows(&self, endpoints: &[&EndpointResult], cli: &CheckArgs) {
+        print_markdown_header(&["Endpoint", "Methods", "Status", "Count", "Files"]);
+
+        for result in endpoints {
+            let status = if result.is_deprecated_in_use() {
+                format!("**{}**", status_label(result))
+            } else {
+                status_label(result).to_string()
             };
 
             let files_str = if result.files.is_empty() {
                 "-".to_string()
             } else if cli.truncate && result.files.len() > 3 {
                 format!("{} files (truncated)", result.files.len())
+            } else if cli.verbose {
+                result.locations.iter()
+                    .map(|l| format!("`{}:{}`", l.file, l.line))
+                    .collect::<Vec<_>>()
+                    .join(", ")
             } else {
                 result.files.iter()
-                    .map(|f| std::path::Path::new(f).file_name()
-                         .and_then(|n| n.to_str())
-                         .unwrap_or(f))
+                    .map(|f| file_label(result, f))
                     .collect::<Vec<_>>()
                     .join(", ")
             };
@@ -259,7 +389,120 @@ impl OutputFormatter {
                      result.usage_count,
                      files_str);
         }
+    }
+}
 
-        Ok(())
+/// Short file name for display, marking files matched only via operationId
+fn file_label(result: &EndpointResult, file: &str) -> String {
+    let filename = std::path::Path::new(file).file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(file);
+
+    if result.operation_id_files.iter().any(|f| f == file) {
+        format!("{} (via operationId)", filename)
+    } else {
+        filename.to_string()
+    }
+}
+
+/// Status column text, flagging deprecated operations
+fn status_label(result: &EndpointResult) -> &'static str {
+    match (&result.status, result.deprecated) {
+        (EndpointStatus::Used, false) => "✓ USED",
+        (EndpointStatus::Unused, false) => "✗ UNUSED",
+        (EndpointStatus::Used, true) => "⚠ USED (DEPRECATED)",
+        (EndpointStatus::Unused, true) => "✗ UNUSED (DEPRECATED)",
+    }
+}
+
+/// List every usage as `file:line:column`, so reports can be clicked through
+fn print_locations(endpoints: &[EndpointResult]) {
+    println!("\nUsage Locations:");
+    for result in endpoints.iter().filter(|r| !r.locations.is_empty()) {
+        println!("  {} {}:", result.endpoint.method.as_str(), result.endpoint.path);
+        for location in &result.locations {
+            println!("    {}:{}:{}  [{}]  {}",
+                     location.file,
+                     location.line,
+                     location.column,
+                     location.pattern,
+                     location.snippet);
+        }
+    }
+}
+
+/// List deprecated endpoints that still have usages, with the full path of every file to migrate
+fn print_deprecation_audit(results: &AnalysisResult, heading_prefix: &str, item_prefix: &str) {
+    let deprecated_in_use = results.deprecated_in_use();
+
+    if deprecated_in_use.is_empty() {
+        println!("{}Deprecation audit: passed (no deprecated endpoints in use)", heading_prefix);
+        return;
     }
-}
\ No newline at end of file
+
+    println!("{}Deprecation audit: {} deprecated endpoint(s) still in use", heading_prefix, deprecated_in_use.len());
+    for result in deprecated_in_use {
+        println!("{}{} {}:", item_prefix, result.endpoint.method.as_str(), result.endpoint.path);
+        for file in &result.files {
+            println!("  {}{}", item_prefix, file);
+        }
+    }
+}
+
+/// List calls whose path matches no spec endpoint, each with its location
+fn print_unknown_calls(results: &AnalysisResult, heading_prefix: &str, item_prefix: &str) {
+    if results.unknown_calls.is_empty() {
+        println!("{}Unknown endpoints: none (every call matches the spec)", heading_prefix);
+        return;
+    }
+
+    println!("{}Unknown endpoints: {} call(s) match no spec path", heading_prefix, results.unknown_calls.len());
+    for call in &results.unknown_calls {
+        println!("{}{} {}  {}  {}",
+                 item_prefix,
+                 call.method.as_str(),
+                 call.url,
+                 location_label(&call.location),
+                 call.location.snippet);
+    }
+}
+
+fn location_label(location: &MatchLocation) -> String {
+    format!("{}:{}:{}", location.file, location.line, location.column)
+}
+
+/// Group name for endpoints without tags
+const UNTAGGED: &str = "(untagged)";
+
+/// Group endpoints by OpenAPI tag; endpoints with several tags appear in each group
+fn group_by_tag(endpoints: &[EndpointResult]) -> BTreeMap<String, Vec<&EndpointResult>> {
+    let tagged: Vec<(&str, &EndpointResult)> = endpoints.iter()
+        .flat_map(|result| {
+            let tags: Vec<&str> = if result.tags.is_empty() {
+                vec![UNTAGGED]
+            } else {
+                result.tags.iter().map(String::as_str).collect()
+            };
+            tags.into_iter().map(move |tag| (tag, result))
+        })
+        .collect();
+
+    grouped(&tagged, |(tag, _)| tag.to_string())
+        .into_iter()
+        .map(|(tag, items)| (tag, items.into_iter().map(|(_, result)| *result).collect()))
+        .collect()
+}
+
+/// Count used endpoints, returning `(used, total)`
+fn coverage(endpoints: &[&EndpointResult]) -> (usize, usize) {
+    let used = endpoints.iter().filter(|r| r.status == EndpointStatus::Used).count();
+    (used, endpoints.len())
+}
+
+fn percentage(used: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (used as f64 / total as f64) * 100.0
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+/// Print the framed header of a table report: its title and `Label: value` lines
+pub fn print_header(title: &str, fields: &[(&str, String)]) {
+    println!("\n{}", "=".repeat(80));
+    println!("{}", title);
+    for (label, value) in fields {
+        println!("{}: {}", label, value);
+    }
+    println!("{}", "=".repeat(80));
+}
+
+/// Print one CSV row with every field quoted
+pub fn print_csv_row(fields: &[&str]) {
+    let quoted: Vec<String> = fields.iter().map(|field| format!("\"{}\"", field.replace('"', "\"\""))).collect();
+    println!("{}", quoted.join(","));
+}
+
+/// Print the heading and separator rows of a Markdown table
+pub fn print_markdown_header(columns: &[&str]) {
+    println!("| {} |", columns.join(" | "));
+    let rules: Vec<String> = columns.iter().map(|column| "-".repeat(column.len() + 2)).collect();
+    println!("|{}|", rules.join("|"));
+}
+
+/// Items of a Markdown table cell, one per line, or `-` when there are none
+pub fn markdown_list(items: &[String]) -> String {
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join("<br>")
+    }
+}
+
+/// Group report items by `key`, in key order
+pub fn grouped<K: Ord, T>(items: &[T], key: impl Fn(&T) -> K) -> BTreeMap<K, Vec<&T>> {
+    let mut groups: BTreeMap<K, Vec<&T>> = BTreeMap::new();
+    for item in items {
+        groups.entry(key(item)).or_default().push(item);
+    }
+    groups
+}
+
+pub fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
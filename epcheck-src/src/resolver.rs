@@ -0,0 +1,517 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use crate::paths::normalize;
+use crate::openapi::HttpMethod;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// Errors raised while resolving `$ref` references
+#[derive(Debug, Error)]
+pub enum RefError {
+    #[error("unresolved reference '{reference}' in {file} at #{location}")]
+    Unresolved {
+        reference: String,
+        file: String,
+        location: String,
+    },
+    #[error("cannot load '{target}' referenced in {file} at #{location}: {reason}")]
+    Load {
+        target: String,
+        file: String,
+        location: String,
+        reason: String,
+    },
+}
+
+/// Git revision a spec is read at (`main:api/openapi.yaml`), so the files it
+/// references are read at the same revision
+#[derive(Debug, Clone)]
+pub struct Revision {
+    rev: String,
+    /// The spec path is relative to the working directory (`./`, `../`)
+    /// rather than to the repository root
+    from_cwd: bool,
+}
+
+impl Revision {
+    pub fn new(rev: &str, spec_path: &Path) -> Self {
+        let from_cwd = matches!(spec_path.components().next(), Some(Component::CurDir | Component::ParentDir));
+        Self { rev: rev.to_string(), from_cwd }
+    }
+
+    /// Contents of `path`, a spec-relative path joined onto the spec path, at this revision
+    pub fn read(&self, path: &Path) -> Result<String, String> {
+        let path = normalize(path);
+        let object = if self.from_cwd {
+            format!("{}:./{}", self.rev, path.display())
+        } else {
+            format!("{}:{}", self.rev, path.display())
+        };
+        let output = std::process::Command::new("git")
+            .args(["show", &object])
+            .output()
+            .map_err(|e| format!("failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    }
+}
+
+/// Resolves the children of a node once the node itself is resolved
+type Children = fn(&mut RefResolver, &Map<String, Value>, &Path, &str) -> Result<Value, RefError>;
+
+/// Resolver that inlines the JSON-pointer references (`#/components/...`) and
+/// relative file references (`./paths/users.yaml#/get`) that endpoint
+/// extraction needs into one document
+pub struct RefResolver {
+    root_file: PathBuf,
+    documents: HashMap<PathBuf, Rc<Value>>,
+    stack: Vec<(PathBuf, String)>,
+    /// Resolved reference targets, by file and pointer
+    resolved: HashMap<(PathBuf, String), Value>,
+    /// Cyclic references left in place so far
+    cycles_cut: usize,
+    revision: Option<Revision>,
+}
+
+impl RefResolver {
+    /// Create a resolver for a document loaded from `root_file`.
+    ///
+    /// Pass an empty path for documents without a local file (e.g. URLs);
+    /// relative file references are then reported as errors.
+    pub fn new(document: Value, root_file: &Path) -> Self {
+        let root_file = root_file.canonicalize().unwrap_or_else(|_| root_file.to_path_buf());
+        let mut documents = HashMap::new();
+        documents.insert(root_file.clone(), Rc::new(document));
+
+        Self {
+            root_file,
+            documents,
+            stack: Vec::new(),
+            resolved: HashMap::new(),
+            cycles_cut: 0,
+            revision: None,
+        }
+    }
+
+    /// Create a resolver for a document read from `root_file` at a git
+    /// revision; relative file references are read at the same revision
+    pub fn at_revision(document: Value, root_file: &Path, revision: Revision) -> Self {
+        let root_file = normalize(root_file);
+        let mut documents = HashMap::new();
+        documents.insert(root_file.clone(), Rc::new(document));
+
+        Self {
+            root_file,
+            documents,
+            stack: Vec::new(),
+            resolved: HashMap::new(),
+            cycles_cut: 0,
+            revision: Some(revision),
+        }
+    }
+
+    /// Resolve the references endpoint extraction looks through: path items,
+    /// parameters, request bodies and responses, wherever they are declared.
+    ///
+    /// Schemas are left as written, so shared schemas are never expanded.
+    /// Cyclic references are left in place as `$ref` objects instead of being
+    /// expanded forever, and remote `http(s)` references are left untouched.
+    pub fn resolve(&mut self) -> Result<Value, RefError> {
+        let file = self.root_file.clone();
+        let root = Rc::clone(&self.documents[&file]);
+        let mut resolved = (*root).clone();
+
+        for section in ["paths", "webhooks"] {
+            if let Some(Value::Object(path_items)) = root.get(section) {
+                let mut items = Map::new();
+                for (path, item) in path_items {
+                    let location = format!("/{}/{}", section, escape_pointer_token(path));
+                    items.insert(path.clone(), self.resolve_node(item, &file, &location, Self::resolve_path_item)?);
+                }
+                resolved[section] = Value::Object(items);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve `value` if it is a reference, then its children with `children`
+    fn resolve_node(&mut self, value: &Value, file: &Path, location: &str, children: Children) -> Result<Value, RefError> {
+        match value {
+            Value::Object(map) => match map.get("$ref") {
+                Some(Value::String(reference)) if !reference.starts_with("http://") && !reference.starts_with("https://") => {
+                    self.resolve_ref(reference, map, file, location, children)
+                }
+                Some(_) => Ok(value.clone()),
+                None => children(self, map, file, location),
+            },
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn resolve_path_item(&mut self, item: &Map<String, Value>, file: &Path, location: &str) -> Result<Value, RefError> {
+        let mut resolved = Map::new();
+        for (key, child) in item {
+            let child_location = format!("{}/{}", location, escape_pointer_token(key));
+            let child = if key == "parameters" {
+                self.resolve_items(child, file, &child_location)?
+            } else if HttpMethod::from_str(key).is_some() {
+                self.resolve_node(child, file, &child_location, Self::resolve_operation)?
+            } else {
+                child.clone()
+            };
+            resolved.insert(key.clone(), child);
+        }
+        Ok(Value::Object(resolved))
+    }
+
+    fn resolve_operation(&mut self, operation: &Map<String, Value>, file: &Path, location: &str) -> Result<Value, RefError> {
+        let mut resolved = Map::new();
+        for (key, child) in operation {
+            let child_location = format!("{}/{}", location, escape_pointer_token(key));
+            let child = match key.as_str() {
+                "parameters" | "responses" => self.resolve_items(child, file, &child_location)?,
+                "requestBody" => self.resolve_node(child, file, &child_location, Self::keep_children)?,
+                _ => child.clone(),
+            };
+            resolved.insert(key.clone(), child);
+        }
+        Ok(Value::Object(resolved))
+    }
+
+    /// Resolve each entry of a parameter list or responses map, but nothing inside them
+    fn resolve_items(&mut self, items: &Value, file: &Path, location: &str) -> Result<Value, RefError> {
+        match items {
+            Value::Array(entries) => {
+                let mut resolved = Vec::with_capacity(entries.len());
+                for (index, entry) in entries.iter().enumerate() {
+                    let entry_location = format!("{}/{}", location, index);
+                    resolved.push(self.resolve_node(entry, file, &entry_location, Self::keep_children)?);
+                }
+                Ok(Value::Array(resolved))
+            }
+            Value::Object(entries) => {
+                let mut resolved = Map::new();
+                for (key, entry) in entries {
+                    let entry_location = format!("{}/{}", location, escape_pointer_token(key));
+                    resolved.insert(key.clone(), self.resolve_node(entry, file, &entry_location, Self::keep_children)?);
+                }
+                Ok(Value::Object(resolved))
+            }
+            _ => Ok(items.clone()),
+        }
+    }
+
+    fn keep_children(&mut self, node: &Map<String, Value>, _file: &Path, _location: &str) -> Result<Value, RefError> {
+        Ok(Value::Object(node.clone()))
+    }
+
+    fn resolve_ref(
+        &mut self,
+        reference: &str,
+        siblings: &Map<String, Value>,
+        file: &Path,
+        location: &str,
+        children: Children,
+    ) -> Result<Value, RefError> {
+        let (target_part, fragment) = match reference.split_once('#') {
+            Some((target, fragment)) => (target, fragment),
+            None => (reference, ""),
+        };
+        let pointer = percent_decode(fragment);
+
+        let target_file = if target_part.is_empty() {
+            file.to_path_buf()
+        } else {
+            self.load_document(target_part, file, location)?
+        };
+
+        // A reference back into something we are currently expanding is a cycle
+        let same_file = target_file == file;
+        let inside_target = same_file
+            && (location == pointer || location.starts_with(&format!("{}/", pointer)));
+        let key = (target_file.clone(), pointer.clone());
+        if inside_target || self.stack.contains(&key) {
+            tracing::debug!("Leaving cyclic reference '{}' in {} at #{} unresolved", reference, file.display(), location);
+            self.cycles_cut += 1;
+            return Ok(Value::Object(siblings.clone()));
+        }
+
+        let mut resolved = match self.resolved.get(&key) {
+            Some(resolved) => resolved.clone(),
+            None => {
+                let document = Rc::clone(&self.documents[&target_file]);
+                let target = document.pointer(&pointer).ok_or_else(|| RefError::Unresolved {
+                    reference: reference.to_string(),
+                    file: file.display().to_string(),
+                    location: location.to_string(),
+                })?;
+
+                let cycles_cut = self.cycles_cut;
+                self.stack.push(key.clone());
+                let resolved = self.resolve_node(target, &target_file, &pointer, children);
+                self.stack.pop();
+                let resolved = resolved?;
+
+                // A cut cycle depends on what was being expanded, so only complete targets are reused
+                if self.cycles_cut == cycles_cut {
+                    self.resolved.insert(key, resolved.clone());
+                }
+                resolved
+            }
+        };
+
+        // OpenAPI 3.1 allows keys such as `summary` and `description` next to `$ref`
+        if let Value::Object(object) = &mut resolved {
+            for (key, child) in siblings {
+                if key != "$ref" {
+                    object.insert(key.clone(), child.clone());
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Load (or reuse) the document a relative file reference points to
+    fn load_document(&mut self, target: &str, file: &Path, location: &str) -> Result<PathBuf, RefError> {
+        let load_error = |reason: String| RefError::Load {
+            target: target.to_string(),
+            file: file.display().to_string(),
+            location: location.to_string(),
+            reason,
+        };
+
+        if file.as_os_str().is_empty() {
+            return Err(load_error("relative file references require a local spec file".to_string()));
+        }
+
+        let base_dir = file.parent().unwrap_or_else(|| Path::new("."));
+        let target_path = base_dir.join(percent_decode(target));
+        let target_path = match &self.revision {
+            Some(_) => normalize(&target_path),
+            None => target_path.canonicalize().map_err(|e| load_error(e.to_string()))?,
+        };
+
+        if !self.documents.contains_key(&target_path) {
+            let content = match &self.revision {
+                Some(revision) => revision.read(&target_path).map_err(load_error)?,
+                None => std::fs::read_to_string(&target_path).map_err(|e| load_error(e.to_string()))?,
+            };
+            let document = parse_document(&content, &target_path).map_err(|e| load_error(e.to_string()))?;
+            self.documents.insert(target_path.clone(), Rc::new(document));
+        }
+
+        Ok(target_path)
+    }
+}
+
+/// Inline the path item, operation, parameter, request body and response
+/// references in an OpenAPI document loaded from `root_file`, at `revision`
+/// if it was read from git
+pub fn resolve_refs(document: Value, root_file: &Path, revision: Option<&Revision>) -> Result<Value, RefError> {
+    match revision {
+        Some(revision) => RefResolver::at_revision(document, root_file, revision.clone()).resolve(),
+        None => RefResolver::new(document, root_file).resolve(),
+    }
+}
+
+/// Parse a JSON or YAML document, using the file extension as a hint
+pub fn parse_document(content: &str, path: &Path) -> anyhow::Result<Value> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+    let document = match extension {
+        "json" => serde_json::from_str(content)?,
+        "yaml" | "yml" => serde_yaml::from_str(content)?,
+        _ => {
+            // Try JSON first, then YAML
+            match serde_json::from_str(content) {
+                Ok(document) => document,
+                Err(_) => serde_yaml::from_str(content)?,
+            }
+        }
+    };
+
+    Ok(document)
+}
+
+/// Escape a key for use as a JSON pointer token (RFC 6901)
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Decode percent-encoded characters in a URI fragment (e.g. `%7Bid%7D`)
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolve(document: Value) -> Result<Value, RefError> {
+        resolve_refs(document, Path::new(""), None)
+    }
+
+    /// Write `files` into a fresh temporary directory and resolve its `openapi.yaml`
+    fn resolve_files(name: &str, files: &[(&str, &str)]) -> Result<Value, RefError> {
+        let dir = std::env::temp_dir().join(format!("epcheck-resolver-{}-{}", std::process::id(), name));
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let root_file = dir.join("openapi.yaml");
+        let document = parse_document(&std::fs::read_to_string(&root_file).unwrap(), &root_file).unwrap();
+        let resolved = resolve_refs(document, &root_file, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+        resolved
+    }
+
+    #[test]
+    fn internal_refs_are_inlined() {
+        let resolved = resolve(json!({
+            "paths": {
+                "/users/{id}": {
+                    "parameters": [{"$ref": "#/components/parameters/Id"}],
+                    "get": {
+                        "requestBody": {"$ref": "#/components/requestBodies/User"},
+                        "responses": {"200": {"$ref": "#/components/responses/User"}}
+                    }
+                }
+            },
+            "components": {
+                "parameters": {"Id": {"name": "id", "in": "path"}},
+                "requestBodies": {"User": {"required": true}},
+                "responses": {"User": {"description": "user", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}}}},
+                "schemas": {"User": {"type": "object"}}
+            }
+        })).unwrap();
+
+        let item = &resolved["paths"]["/users/{id}"];
+        assert_eq!(item["parameters"][0], json!({"name": "id", "in": "path"}));
+        assert_eq!(item["get"]["requestBody"], json!({"required": true}));
+        assert_eq!(item["get"]["responses"]["200"]["description"], "user");
+        // Schemas are left as written
+        assert_eq!(item["get"]["responses"]["200"]["content"]["application/json"]["schema"],
+                   json!({"$ref": "#/components/schemas/User"}));
+    }
+
+    #[test]
+    fn siblings_override_the_target() {
+        let resolved = resolve(json!({
+            "paths": {
+                "/users": {"$ref": "#/x-shared/users", "summary": "Users"},
+                "/people": {"$ref": "#/x-shared/users"}
+            },
+            "x-shared": {"users": {"summary": "Shared", "get": {"operationId": "listUsers"}}}
+        })).unwrap();
+
+        assert_eq!(resolved["paths"]["/users"], json!({"summary": "Users", "get": {"operationId": "listUsers"}}));
+        // The memoized target is not changed by the siblings merged into one use of it
+        assert_eq!(resolved["paths"]["/people"]["summary"], "Shared");
+    }
+
+    #[test]
+    fn cycles_are_left_in_place() {
+        let resolved = resolve(json!({
+            "paths": {
+                "/self": {"$ref": "#/paths/~1self"},
+                "/a": {"$ref": "#/paths/~1b"},
+                "/b": {"$ref": "#/paths/~1a"},
+                "/ok": {"get": {"parameters": [{"$ref": "#/paths/~1ok/get/parameters/0"}]}}
+            }
+        })).unwrap();
+
+        assert_eq!(resolved["paths"]["/self"], json!({"$ref": "#/paths/~1self"}));
+        assert_eq!(resolved["paths"]["/a"], json!({"$ref": "#/paths/~1b"}));
+        assert_eq!(resolved["paths"]["/b"], json!({"$ref": "#/paths/~1a"}));
+        assert_eq!(resolved["paths"]["/ok"]["get"]["parameters"][0], json!({"$ref": "#/paths/~1ok/get/parameters/0"}));
+    }
+
+    #[test]
+    fn remote_refs_are_left_in_place() {
+        let resolved = resolve(json!({
+            "paths": {"/users": {"$ref": "https://example.com/paths.yaml#/users"}}
+        })).unwrap();
+
+        assert_eq!(resolved["paths"]["/users"], json!({"$ref": "https://example.com/paths.yaml#/users"}));
+    }
+
+    #[test]
+    fn unresolved_refs_are_errors() {
+        let missing = resolve(json!({
+            "paths": {"/users": {"get": {"parameters": [{"$ref": "#/components/parameters/Missing"}]}}}
+        }));
+        assert!(matches!(missing, Err(RefError::Unresolved { location, .. }) if location == "/paths/~1users/get/parameters/0"));
+
+        let no_file = resolve(json!({
+            "paths": {"/users": {"$ref": "./users.yaml#/users"}}
+        }));
+        assert!(matches!(no_file, Err(RefError::Load { .. })));
+    }
+    #[test]
+    fn relative_file_refs_are_inlined() {
+        let resolved = resolve_files("relative", &[
+            ("openapi.yaml", "paths:\n  /users/{id}:\n    $ref: 'paths/users.yaml#/user'\n"),
+            ("paths/users.yaml", concat!(
+                "user:\n",
+                "  get:\n",
+                "    parameters:\n",
+                "      - $ref: '../components.yaml#/parameters/Id'\n",
+                "    responses:\n",
+                "      '200':\n",
+                "        $ref: '#/responses/Ok'\n",
+                "responses:\n",
+                "  Ok:\n",
+                "    description: ok\n",
+            )),
+            ("components.yaml", "parameters:\n  Id:\n    name: id\n    in: path\n"),
+        ]).unwrap();
+
+        let get = &resolved["paths"]["/users/{id}"]["get"];
+        assert_eq!(get["parameters"][0], json!({"name": "id", "in": "path"}));
+        // `#/...` inside a referenced file points into that file
+        assert_eq!(get["responses"]["200"], json!({"description": "ok"}));
+    }
+
+    #[test]
+    fn cross_file_cycles_are_left_in_place() {
+        let resolved = resolve_files("cycle", &[
+            ("openapi.yaml", "paths:\n  /loop:\n    $ref: 'loop.yaml#/item'\n  /users:\n    get: {}\n"),
+            ("loop.yaml", "item:\n  $ref: 'openapi.yaml#/paths/~1loop'\n"),
+        ]).unwrap();
+
+        assert_eq!(resolved["paths"]["/loop"], json!({"$ref": "loop.yaml#/item"}));
+        assert_eq!(resolved["paths"]["/users"], json!({"get": {}}));
+    }
+
+    #[test]
+    fn missing_files_are_load_errors() {
+        let missing = resolve_files("missing", &[
+            ("openapi.yaml", "paths:\n  /users:\n    $ref: 'users.yaml#/users'\n"),
+        ]);
+        assert!(matches!(missing, Err(RefError::Load { target, .. }) if target == "users.yaml"));
+    }
+}
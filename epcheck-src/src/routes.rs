@@ -0,0 +1,722 @@
+use crate::cli::{OutputFormat, RoutesArgs};
+use crate::detector::LineIndex;
+use crate::openapi::{Endpoint, HttpMethod, OpenApiSpec};
+use crate::report::{print_csv_row, print_header, print_markdown_header};
+use crate::scanner::FileScanner;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A route declared in server code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub method: HttpMethod,
+    /// Route template with parameters written as `{name}` (`/users/{id}`)
+    pub path: String,
+    /// 1-based line of the declaration
+    pub line: usize,
+    /// 1-based column of the declaration
+    pub column: usize,
+    /// Which extractor found it (`express`, `aspnet`, `fastapi`, ...)
+    pub framework: &'static str,
+}
+
+/// A route and the file declaring it
+#[derive(Debug, Clone)]
+pub struct RouteDeclaration {
+    pub route: Route,
+    pub file: String,
+}
+
+/// Extracts route declarations from the server code of one framework family
+pub trait RouteExtractor: Send + Sync {
+    /// File extensions (without the dot) this extractor handles
+    fn extensions(&self) -> &[&str];
+
+    fn extract(&self, content: &str) -> Vec<Route>;
+}
+
+/// Express, ASP.NET, FastAPI/Flask and Spring extractors
+pub fn builtin_extractors() -> Vec<Box<dyn RouteExtractor>> {
+    vec![
+        Box::new(ExpressRoutes::new()),
+        Box::new(AspNetRoutes::new()),
+        Box::new(PythonRoutes::new()),
+        Box::new(SpringRoutes::new()),
+    ]
+}
+
+/// `app.get('/users/:id', handler)`, `router.post('/users', ...)`.
+///
+/// Only receivers named like an app or router count, and a handler argument
+/// must follow the path, so client calls such as `api.get('/users')` don't.
+/// Mount prefixes from `app.use('/api', router)` are not applied.
+pub struct ExpressRoutes {
+    pattern: Regex,
+}
+
+impl ExpressRoutes {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(
+                r#"\b(?:app|server|routes|\w*[Rr]outer)\s*\.\s*(get|post|put|delete|patch|head|options)\s*\(\s*['"`]([^'"`]*)['"`]\s*,"#,
+            ).expect("built-in route pattern"),
+        }
+    }
+}
+
+impl Default for ExpressRoutes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteExtractor for ExpressRoutes {
+    fn extensions(&self) -> &[&str] {
+        &["js", "jsx", "ts", "tsx", "mjs", "cjs"]
+    }
+
+    fn extract(&self, content: &str) -> Vec<Route> {
+        let lines = LineIndex::new(content);
+        self.pattern.captures_iter(content)
+            .filter_map(|captures| {
+                let whole = captures.get(0)?;
+                route(&lines, content, whole.start(), &captures[1], &captures[2], "express")
+            })
+            .collect()
+    }
+}
+
+/// `[HttpGet("{id}")]` actions under a controller's `[Route("api/[controller]")]`,
+/// and minimal-API `app.MapGet("/users/{id}", ...)`. `[action]` stands for the
+/// name of the method the attribute is on, without an `Async` suffix.
+pub struct AspNetRoutes {
+    controller: Regex,
+    action: Regex,
+    /// Name of the method declared after an action attribute and any further attributes
+    method_name: Regex,
+    minimal: Regex,
+}
+
+impl AspNetRoutes {
+    pub fn new() -> Self {
+        Self {
+            controller: Regex::new(
+                r#"(?:\[\s*Route\s*\(\s*"([^"]*)"\s*\)\s*\](?:\s*\[[^\]]*\])*\s*)?(?:(?:public|internal|sealed|abstract|partial|static)\s+)*class\s+(\w+)"#,
+            ).expect("built-in route pattern"),
+            action: Regex::new(
+                r#"\[\s*Http(Get|Post|Put|Delete|Patch|Head|Options)\s*(?:\(\s*(?:template\s*:\s*)?"([^"]*)"[^\]]*)?\]"#,
+            ).expect("built-in route pattern"),
+            method_name: Regex::new(r"^(?:\s*\[[^\]]*\])*[^(\[{;]*?\b(\w+)\s*(?:<[^()]*>)?\s*\(")
+                .expect("built-in route pattern"),
+            minimal: Regex::new(r#"\.\s*Map(Get|Post|Put|Delete|Patch)\s*\(\s*"([^"]*)""#)
+                .expect("built-in route pattern"),
+        }
+    }
+}
+
+impl Default for AspNetRoutes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteExtractor for AspNetRoutes {
+    fn extensions(&self) -> &[&str] {
+        &["cs"]
+    }
+
+    fn extract(&self, content: &str) -> Vec<Route> {
+        let lines = LineIndex::new(content);
+        let classes: Vec<ClassScope> = self.controller.captures_iter(content)
+            .filter_map(|captures| {
+                let name = &captures[2];
+                let controller = name.strip_suffix("Controller").unwrap_or(name);
+                Some(ClassScope {
+                    start: captures.get(0)?.start(),
+                    prefix: captures.get(1)
+                        .map(|prefix| prefix.as_str().replace("[controller]", controller))
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let mut routes = Vec::new();
+        for captures in self.action.captures_iter(content) {
+            let whole = match captures.get(0) {
+                Some(whole) => whole,
+                None => continue,
+            };
+            let start = whole.start();
+            let template = captures.get(2).map_or("", |template| template.as_str());
+            // `~/` and `/` templates ignore the controller prefix
+            let mut path = if template.starts_with('/') || template.starts_with("~/") {
+                template.trim_start_matches('~').to_string()
+            } else {
+                join_paths(&enclosing_prefix(&classes, start), template)
+            };
+            if path.contains("[action]") {
+                if let Some(name) = self.method_name.captures(&content[whole.end()..]) {
+                    let name = name[1].strip_suffix("Async").unwrap_or(&name[1]);
+                    path = path.replace("[action]", name);
+                }
+            }
+            routes.extend(route(&lines, content, start, &captures[1], &path, "aspnet"));
+        }
+        for captures in self.minimal.captures_iter(content) {
+            if let Some(whole) = captures.get(0) {
+                routes.extend(route(&lines, content, whole.start(), &captures[1], &captures[2], "aspnet-minimal"));
+            }
+        }
+        routes
+    }
+}
+
+/// FastAPI and Flask decorators: `@app.get("/users/{id}")`,
+/// `@router.post(...)` with an `APIRouter(prefix=...)` or
+/// `Blueprint(..., url_prefix=...)` prefix, and
+/// `@app.route("/users/<int:id>", methods=["GET", "PUT"])`
+pub struct PythonRoutes {
+    shortcut: Regex,
+    route: Regex,
+    methods: Regex,
+    prefix: Regex,
+}
+
+impl PythonRoutes {
+    pub fn new() -> Self {
+        Self {
+            shortcut: Regex::new(
+                r#"(?m)^[ \t]*@\s*(\w+)\.(get|post|put|delete|patch|head|options)\s*\(\s*(?:path\s*=\s*)?['"]([^'"]*)['"]"#,
+            ).expect("built-in route pattern"),
+            route: Regex::new(r#"(?m)^[ \t]*@\s*(\w+)\.route\s*\(\s*['"]([^'"]*)['"]([^)]*)\)"#)
+                .expect("built-in route pattern"),
+            methods: Regex::new(r#"methods\s*=\s*[\[(]([^\])]*)[\])]"#).expect("built-in route pattern"),
+            prefix: Regex::new(
+                r#"(\w+)\s*=\s*(?:APIRouter|Blueprint)\s*\([^)]*?\b(?:url_)?prefix\s*=\s*['"]([^'"]*)['"]"#,
+            ).expect("built-in route pattern"),
+        }
+    }
+}
+
+impl Default for PythonRoutes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteExtractor for PythonRoutes {
+    fn extensions(&self) -> &[&str] {
+        &["py"]
+    }
+
+    fn extract(&self, content: &str) -> Vec<Route> {
+        let lines = LineIndex::new(content);
+        let prefixes: HashMap<&str, &str> = self.prefix.captures_iter(content)
+            .filter_map(|captures| Some((captures.get(1)?.as_str(), captures.get(2)?.as_str())))
+            .collect();
+        let prefixed = |receiver: &str, path: &str| join_paths(prefixes.get(receiver).copied().unwrap_or(""), path);
+
+        let mut routes = Vec::new();
+        for captures in self.shortcut.captures_iter(content) {
+            if let Some(whole) = captures.get(0) {
+                let path = prefixed(&captures[1], &captures[3]);
+                routes.extend(route(&lines, content, whole.start(), &captures[2], &path, "fastapi"));
+            }
+        }
+        for captures in self.route.captures_iter(content) {
+            let start = match captures.get(0) {
+                Some(whole) => whole.start(),
+                None => continue,
+            };
+            let path = prefixed(&captures[1], &captures[2]);
+            let methods: Vec<String> = match self.methods.captures(&captures[3]) {
+                Some(list) => list[1].split(',')
+                    .map(|method| method.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+                    .filter(|method| !method.is_empty())
+                    .collect(),
+                None => vec!["GET".to_string()],
+            };
+            for method in methods {
+                routes.extend(route(&lines, content, start, &method, &path, "flask"));
+            }
+        }
+        routes
+    }
+}
+
+/// Spring `@GetMapping("/{id}")` and `@RequestMapping(value = "/x", method = RequestMethod.POST)`
+/// handlers under a class-level `@RequestMapping("/api/users")`
+pub struct SpringRoutes {
+    class: Regex,
+    mapping: Regex,
+    named_path: Regex,
+    named_argument: Regex,
+    string: Regex,
+    request_method: Regex,
+}
+
+impl SpringRoutes {
+    pub fn new() -> Self {
+        Self {
+            class: Regex::new(
+                r#"(?:@RequestMapping\s*\(([^)]*)\)(?:\s*@\w+(?:\s*\([^)]*\))?)*\s*)?(?:(?:public|private|protected|abstract|final|open|data)\s+)*(?:class|interface)\s+\w+"#,
+            ).expect("built-in route pattern"),
+            mapping: Regex::new(r#"@(Get|Post|Put|Delete|Patch|Request)Mapping\b(?:\s*\(([^)]*)\))?"#)
+                .expect("built-in route pattern"),
+            named_path: Regex::new(r#"\b(?:value|path)\s*=\s*(\{[^}]*\}|\[[^\]]*\]|"[^"]*")"#)
+                .expect("built-in route pattern"),
+            named_argument: Regex::new(r"\b\w+\s*=").expect("built-in route pattern"),
+            string: Regex::new(r#""([^"]*)""#).expect("built-in route pattern"),
+            request_method: Regex::new(r#"RequestMethod\.(\w+)"#).expect("built-in route pattern"),
+        }
+    }
+
+    /// Paths named in a mapping annotation's arguments; `""` when there are none
+    fn paths(&self, arguments: &str) -> Vec<String> {
+        // `value = ...`/`path = ...`, else the unnamed first argument
+        let source = match self.named_path.captures(arguments) {
+            Some(named) => named.get(1).map_or("", |value| value.as_str()),
+            None => match self.named_argument.find(arguments) {
+                Some(name) => &arguments[..name.start()],
+                None => arguments,
+            },
+        };
+
+        let paths: Vec<String> = self.string.captures_iter(source).map(|path| path[1].to_string()).collect();
+        if paths.is_empty() {
+            vec![String::new()]
+        } else {
+            paths
+        }
+    }
+}
+
+impl Default for SpringRoutes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteExtractor for SpringRoutes {
+    fn extensions(&self) -> &[&str] {
+        &["java", "kt"]
+    }
+
+    fn extract(&self, content: &str) -> Vec<Route> {
+        let lines = LineIndex::new(content);
+        let classes: Vec<ClassScope> = self.class.captures_iter(content)
+            .filter_map(|captures| {
+                Some(ClassScope {
+                    start: captures.get(0)?.start(),
+                    prefix: captures.get(1)
+                        .and_then(|arguments| self.paths(arguments.as_str()).into_iter().next())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+        // Class-level @RequestMapping annotations are prefixes, not handlers
+        let class_annotations: HashSet<usize> = classes.iter().map(|class| class.start).collect();
+
+        let mut routes = Vec::new();
+        for captures in self.mapping.captures_iter(content) {
+            let start = match captures.get(0) {
+                Some(whole) => whole.start(),
+                None => continue,
+            };
+            if class_annotations.contains(&start) {
+                continue;
+            }
+            let arguments = captures.get(2).map_or("", |arguments| arguments.as_str());
+            let methods: Vec<String> = match &captures[1] {
+                "Request" => self.request_method.captures_iter(arguments).map(|method| method[1].to_string()).collect(),
+                method => vec![method.to_string()],
+            };
+
+            let prefix = enclosing_prefix(&classes, start);
+            for path in self.paths(arguments) {
+                let path = join_paths(&prefix, &path);
+                for method in &methods {
+                    routes.extend(route(&lines, content, start, method, &path, "spring"));
+                }
+            }
+        }
+        routes
+    }
+}
+
+/// A class declaration and the route prefix it gives its handlers
+struct ClassScope {
+    start: usize,
+    prefix: String,
+}
+
+/// Prefix of the last class declared before `offset`
+fn enclosing_prefix(classes: &[ClassScope], offset: usize) -> String {
+    classes.iter()
+        .take_while(|class| class.start <= offset)
+        .last()
+        .map(|class| class.prefix.clone())
+        .unwrap_or_default()
+}
+
+fn join_paths(prefix: &str, path: &str) -> String {
+    match (prefix.trim_end_matches('/'), path.trim_start_matches('/')) {
+        ("", path) => path.to_string(),
+        (prefix, "") => prefix.to_string(),
+        (prefix, path) => format!("{}/{}", prefix, path),
+    }
+}
+
+fn route(lines: &LineIndex, content: &str, offset: usize, method: &str, path: &str, framework: &'static str) -> Option<Route> {
+    let method = HttpMethod::from_str(method)?;
+    let (line, column) = lines.position(content, offset);
+    Some(Route { method, path: normalize_route(path), line, column, framework })
+}
+
+/// Route template with a leading slash, no trailing slash, and every
+/// parameter syntax (`:id`, `<int:id>`, `{id:int}`, `{id?}`) written as `{id}`
+fn normalize_route(path: &str) -> String {
+    let segments: Vec<String> = path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(normalize_segment)
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+fn normalize_segment(segment: &str) -> String {
+    let name = if let Some(name) = segment.strip_prefix(':') {
+        name
+    } else if let Some(inner) = segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+        inner.rsplit(':').next().unwrap_or(inner)
+    } else if let Some(inner) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+        inner.trim_start_matches('*').split(':').next().unwrap_or(inner)
+    } else {
+        return segment.to_string();
+    };
+    format!("{{{}}}", name.trim_end_matches('?'))
+}
+
+/// Comparison key: parameter names and letter case don't matter
+fn route_key(method: HttpMethod, path: &str) -> (HttpMethod, String) {
+    (method, crate::diff::template_key(path).to_lowercase())
+}
+
+/// Declared routes compared with the spec
+#[derive(Debug, Clone)]
+pub struct RouteReport {
+    pub routes: Vec<RouteDeclaration>,
+    /// Routes with no matching spec operation
+    pub undocumented: Vec<RouteDeclaration>,
+    /// Spec operations with no matching route
+    pub unimplemented: Vec<Endpoint>,
+}
+
+/// Extract the routes declared in every source file under `dir`
+pub fn extract_routes(
+    files: &FileScanner,
+    dir: &Path,
+    extractors: &[Box<dyn RouteExtractor>],
+) -> anyhow::Result<Vec<RouteDeclaration>> {
+    let by_extension: HashMap<&str, Vec<&dyn RouteExtractor>> = extractors.iter()
+        .flat_map(|extractor| extractor.extensions().iter().map(move |ext| (*ext, extractor.as_ref())))
+        .fold(HashMap::new(), |mut map, (ext, extractor)| {
+            map.entry(ext).or_default().push(extractor);
+            map
+        });
+
+    let found = Mutex::new(Vec::new());
+    let (by_extension, found_ref) = (&by_extension, &found);
+    files.walk_parallel(dir, || {
+        move |path: PathBuf| {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            let extractors = match by_extension.get(extension) {
+                Some(extractors) => extractors,
+                None => return,
+            };
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => return,
+            };
+
+            let file = path.to_string_lossy().to_string();
+            let routes: Vec<RouteDeclaration> = extractors.iter()
+                .flat_map(|extractor| extractor.extract(&content))
+                .map(|route| RouteDeclaration { route, file: file.clone() })
+                .collect();
+            found_ref.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend(routes);
+        }
+    })?;
+
+    let mut routes = found.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    routes.sort_by(|a, b| {
+        (&a.route.path, a.route.method.as_str(), &a.file, a.route.line)
+            .cmp(&(&b.route.path, b.route.method.as_str(), &b.file, b.route.line))
+    });
+    Ok(routes)
+}
+
+/// Match routes to spec operations on method and path template. Operations
+/// match with or without the spec's server/basePath prefixes.
+pub fn compare_routes(spec: &OpenApiSpec, routes: Vec<RouteDeclaration>) -> RouteReport {
+    let base_paths = spec.base_paths();
+    let mut spec_keys: HashMap<(HttpMethod, String), Vec<Endpoint>> = HashMap::new();
+    for endpoint in crate::openapi::extract_endpoints(spec) {
+        let candidates = std::iter::once(endpoint.path.clone())
+            .chain(base_paths.iter().map(|prefix| format!("{}{}", prefix, endpoint.path)));
+        for path in candidates {
+            spec_keys.entry(route_key(endpoint.method, &path)).or_default().push(endpoint.clone());
+        }
+    }
+
+    let mut implemented: HashSet<Endpoint> = HashSet::new();
+    let mut undocumented = Vec::new();
+    for declaration in &routes {
+        match spec_keys.get(&route_key(declaration.route.method, &declaration.route.path)) {
+            Some(endpoints) => implemented.extend(endpoints.iter().cloned()),
+            None => undocumented.push(declaration.clone()),
+        }
+    }
+
+    let mut unimplemented: Vec<Endpoint> = crate::openapi::extract_endpoints(spec)
+        .into_iter()
+        .filter(|endpoint| !implemented.contains(endpoint))
+        .collect();
+    unimplemented.sort_by(|a, b| (&a.path, a.method.as_str()).cmp(&(&b.path, b.method.as_str())));
+
+    RouteReport { routes, undocumented, unimplemented }
+}
+
+/// Print the route report in the requested format
+pub fn print_routes(report: &RouteReport, args: &RoutesArgs) -> anyhow::Result<()> {
+    match args.check.output_format() {
+        OutputFormat::Table => print_table(report, args),
+        OutputFormat::Csv => print_csv(report),
+        OutputFormat::Json => print_json(report, args)?,
+        OutputFormat::Markdown => print_markdown(report),
+    }
+    Ok(())
+}
+
+fn print_table(report: &RouteReport, args: &RoutesArgs) {
+    print_header("Server Routes vs Spec", &[
+        ("Search Dir", args.check.dir.display().to_string()),
+        ("Routes Found", report.routes.len().to_string()),
+    ]);
+
+    println!("\nUndocumented routes ({}):", report.undocumented.len());
+    for declaration in &report.undocumented {
+        println!("  + {} {}  [{}]", declaration.route.method.as_str(), declaration.route.path, declaration.route.framework);
+        println!("      {}:{}:{}", declaration.file, declaration.route.line, declaration.route.column);
+    }
+
+    println!("\nSpec operations without a handler ({}):", report.unimplemented.len());
+    for endpoint in &report.unimplemented {
+        println!("  - {} {}", endpoint.method.as_str(), endpoint.path);
+    }
+
+    println!("\nSummary:");
+    println!("  Routes: {}", report.routes.len());
+    println!("  Undocumented: {}", report.undocumented.len());
+    println!("  Without handler: {}", report.unimplemented.len());
+}
+
+fn print_csv(report: &RouteReport) {
+    println!("Status,Method,Endpoint,Framework,File,Line");
+    for declaration in &report.undocumented {
+        print_csv_row(&[
+            "undocumented",
+            declaration.route.method.as_str(),
+            &declaration.route.path,
+            declaration.route.framework,
+            &declaration.file,
+            &declaration.route.line.to_string(),
+        ]);
+    }
+    for endpoint in &report.unimplemented {
+        print_csv_row(&["no handler", endpoint.method.as_str(), &endpoint.path, "", "", ""]);
+    }
+}
+
+fn print_json(report: &RouteReport, args: &RoutesArgs) -> anyhow::Result<()> {
+    use serde_json::json;
+
+    let route_json = |declaration: &RouteDeclaration| json!({
+        "method": declaration.route.method.as_str(),
+        "path": declaration.route.path,
+        "framework": declaration.route.framework,
+        "file": declaration.file,
+        "line": declaration.route.line,
+        "column": declaration.route.column
+    });
+
+    let output = json!({
+        "routes": {
+            "generated": chrono::Utc::now().to_rfc3339(),
+            "search_dir": args.check.dir.to_string_lossy(),
+            "total": report.routes.len()
+        },
+        "undocumented": report.undocumented.iter().map(route_json).collect::<Vec<_>>(),
+        "unimplemented": report.unimplemented.iter()
+            .map(|endpoint| json!({ "method": endpoint.method.as_str(), "endpoint": endpoint.path }))
+            .collect::<Vec<_>>()
+    });
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_markdown(report: &RouteReport) {
+    println!("## Server routes vs spec");
+
+    println!("\n### Undocumented routes ({})\n", report.undocumented.len());
+    print_markdown_header(&["Endpoint", "Methods", "Framework", "Location"]);
+    for declaration in &report.undocumented {
+        println!("| {} | {} | {} | `{}:{}` |",
+                 declaration.route.path,
+                 declaration.route.method.as_str(),
+                 declaration.route.framework,
+                 declaration.file,
+                 declaration.route.line);
+    }
+
+    println!("\n### Spec operations without a handler ({})\n", report.unimplemented.len());
+    print_markdown_header(&["Endpoint", "Methods"]);
+    for endpoint in &report.unimplemented {
+        println!("| {} | {} |", endpoint.path, endpoint.method.as_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `METHOD /path` of every extracted route
+    fn routes(extractor: &dyn RouteExtractor, content: &str) -> Vec<String> {
+        extractor.extract(content).iter()
+            .map(|route| format!("{} {}", route.method.as_str(), route.path))
+            .collect()
+    }
+
+    #[test]
+    fn parameter_syntaxes_are_normalized() {
+        assert_eq!(normalize_route("users/:id/"), "/users/{id}");
+        assert_eq!(normalize_route("/users/<int:user_id>"), "/users/{user_id}");
+        assert_eq!(normalize_route("/users/{id:int}/posts/{slug?}"), "/users/{id}/posts/{slug}");
+        assert_eq!(normalize_route("/files/{*path}"), "/files/{path}");
+        assert_eq!(normalize_route("//api//health"), "/api/health");
+        assert_eq!(normalize_route(""), "/");
+    }
+
+    #[test]
+    fn express_routes_need_a_router_and_a_handler() {
+        let content = r#"
+            app.get('/users/:id', getUser);
+            userRouter.post("/users", validate, createUser);
+            api.get('/users');
+            router.delete(`/users/:id`)
+        "#;
+
+        assert_eq!(routes(&ExpressRoutes::new(), content), ["GET /users/{id}", "POST /users"]);
+    }
+
+    #[test]
+    fn aspnet_routes_use_the_controller_prefix() {
+        let content = r#"
+            [ApiController]
+            [Route("api/[controller]")]
+            public class UsersController : ControllerBase
+            {
+                [HttpGet("{id:int}")]
+                public ActionResult<User> Get(int id) => Ok();
+
+                [HttpPost]
+                [ProducesResponseType(201)]
+                public async Task<IActionResult> Create(User user) => Ok();
+
+                [HttpGet("[action]/{id}")]
+                [ProducesResponseType(typeof(User), 200)]
+                public async Task<ActionResult<User>> ProfileAsync(string id) => Ok();
+
+                [HttpDelete("~/admin/users/{id}")]
+                public IActionResult Remove(int id) => Ok();
+            }
+
+            [Route("api/[controller]/[action]")]
+            public class ReportsController : ControllerBase
+            {
+                [HttpGet]
+                public IActionResult Daily() => Ok();
+            }
+
+            app.MapGet("/health", () => "ok");
+        "#;
+
+        let mut found = routes(&AspNetRoutes::new(), content);
+        found.sort();
+        assert_eq!(found, [
+            "DELETE /admin/users/{id}",
+            "GET /api/Reports/Daily",
+            "GET /api/Users/Profile/{id}",
+            "GET /api/Users/{id}",
+            "GET /health",
+            "POST /api/Users",
+        ]);
+    }
+
+    #[test]
+    fn python_routes_apply_router_prefixes() {
+        let content = r#"
+router = APIRouter(prefix="/users")
+bp = Blueprint("admin", __name__, url_prefix="/admin")
+
+@router.get("/{user_id}")
+async def get_user(user_id: int): ...
+
+@app.post(path="/login")
+def login(): ...
+
+@bp.route("/users/<int:id>", methods=["GET", "DELETE"])
+def admin_user(id): ...
+
+@app.route("/")
+def index(): ...
+"#;
+
+        let mut found = routes(&PythonRoutes::new(), content);
+        found.sort();
+        assert_eq!(found, ["DELETE /admin/users/{id}", "GET /", "GET /admin/users/{id}", "GET /users/{user_id}", "POST /login"]);
+    }
+
+    #[test]
+    fn spring_routes_use_the_class_mapping() {
+        let content = r#"
+            @RestController
+            @RequestMapping("/api/users")
+            public class UserController {
+                @GetMapping("/{id}")
+                public User get(@PathVariable long id) { return null; }
+
+                @PostMapping
+                public User create(@RequestBody User user) { return null; }
+
+                @RequestMapping(value = {"/search", "/find"}, method = RequestMethod.GET)
+                public List<User> search() { return null; }
+
+                @DeleteMapping(path = "/{id}", produces = "application/json")
+                public void delete(@PathVariable long id) { }
+            }
+        "#;
+
+        let mut found = routes(&SpringRoutes::new(), content);
+        found.sort();
+        assert_eq!(found, [
+            "DELETE /api/users/{id}",
+            "GET /api/users/find",
+            "GET /api/users/search",
+            "GET /api/users/{id}",
+            "POST /api/users",
+        ]);
+    }
+}
@@ -1,60 +1,135 @@
+use crate::cache::{content_hash, CacheEntry, CacheWriter, FileStamp, ScanCache};
 use crate::cli::CheckArgs;
-use ignore::WalkBuilder;
+use crate::constants::ConstantTable;
+use crate::detector::{DetectedCall, DetectorRegistry, LineIndex};
+use crate::report::capitalize;
+use crate::sourcemap::{BundleMode, SourceMap};
+use crate::syntax::{snippet, CallArgument, CallSite, SourceFile};
+use crate::template::{Specificity, UrlTemplate};
+use aho_corasick::AhoCorasick;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
-use tokio::task;
+use once_cell::sync::OnceCell;
+use std::sync::{mpsc, Arc};
+
+/// Common source file extensions
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "js", "ts", "jsx", "tsx", "py", "rb", "php", "java", "scala", "kt", "swift",
+    "go", "rs", "cpp", "c", "h", "hpp", "cs", "fs", "vb", "clj", "cljs", "elm",
+    "ex", "exs", "hs", "ml", "fsx", "dart", "lua", "pl", "pm", "tcl", "r",
+    "sh", "bash", "zsh", "fish", "ps1", "sql", "xml", "json", "yaml", "yml",
+    "toml", "ini", "cfg", "conf", "md", "txt", "html", "htm", "css", "scss",
+    "sass", "less", "vue", "svelte", "astro"
+];
 
 /// File scanner for finding source files
+#[derive(Clone)]
 pub struct FileScanner {
     cli: CheckArgs,
+    /// Extensions handled by usage detectors, scanned in addition to the built-in list
+    detector_extensions: Vec<String>,
 }
 
 impl FileScanner {
     pub fn new(cli: CheckArgs) -> Self {
-        Self { cli }
+        Self { cli, detector_extensions: Vec::new() }
     }
 
-    /// Find all relevant source files in the directory
-    pub fn find_files(&self, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
+    /// Also pick up files handled by the given usage detectors
+    pub fn with_detectors(mut self, detectors: &DetectorRegistry) -> Self {
+        self.detector_extensions = detectors.extensions();
+        self
+    }
 
-        // Common source file extensions
-        let extensions = [
-            "js", "ts", "jsx", "tsx", "py", "rb", "php", "java", "scala", "kt", "swift",
-            "go", "rs", "cpp", "c", "h", "hpp", "cs", "fs", "vb", "clj", "cljs", "elm",
-            "ex", "exs", "hs", "ml", "fsx", "dart", "lua", "pl", "pm", "tcl", "r",
-            "sh", "bash", "zsh", "fish", "ps1", "sql", "xml", "json", "yaml", "yml",
-            "toml", "ini", "cfg", "conf", "md", "txt", "html", "htm", "css", "scss",
-            "sass", "less", "vue", "svelte", "astro"
-        ];
+    /// Scanning threads: `--threads`, or one per CPU
+    pub fn threads(&self) -> usize {
+        self.cli.threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+            .max(1)
+    }
 
+    fn walk_builder(&self, dir: &Path) -> anyhow::Result<WalkBuilder> {
         let mut builder = WalkBuilder::new(dir);
         builder
             .hidden(false) // Include hidden files
             .git_ignore(true) // Respect .gitignore
             .git_global(true) // Respect global gitignore
-            .git_exclude(true); // Respect .git/info/exclude
+            .git_exclude(true) // Respect .git/info/exclude
+            .filter_entry(|entry| entry.file_name() != crate::cache::CACHE_DIR_NAME);
+
+        builder.overrides(self.overrides(dir)?);
+
+        Ok(builder)
+    }
 
-        // Add custom excludes
+    /// Include/exclude globs from the project config and --exclude
+    fn overrides(&self, dir: &Path) -> anyhow::Result<Override> {
+        let mut overrides = OverrideBuilder::new(dir);
+        for include in &self.cli.project.include {
+            overrides.add(include)?;
+        }
         for exclude in &self.cli.exclude {
-            builder.add_ignore(exclude.clone());
+            overrides.add(&format!("!{}", exclude))?;
+        }
+        Ok(overrides.build()?)
+    }
+
+    fn is_source_file(&self, entry: &DirEntry) -> bool {
+        entry.file_type().is_some_and(|ft| ft.is_file())
+            && self.has_source_name(entry.path())
+            && entry.metadata().is_ok_and(|metadata| self.accepts_size(entry.path(), metadata.len()))
+    }
+
+    fn has_source_name(&self, path: &Path) -> bool {
+        match path.extension() {
+            Some(ext) => {
+                let ext = ext.to_str().unwrap_or("");
+                SOURCE_EXTENSIONS.contains(&ext)
+                    || self.detector_extensions.iter().any(|e| e == ext)
+                    || self.cli.project.extensions.iter().any(|e| e.trim_start_matches('.') == ext)
+            }
+            // Include files without extensions (scripts)
+            None => path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| !name.contains('.')),
         }
+    }
 
-        for result in builder.build() {
+    /// Predicate for paths relative to `dir` that a walk would scan: source
+    /// files not excluded by the include/exclude globs. Used for file lists
+    /// that don't come from walking the tree (git diffs).
+    pub fn path_filter(&self, dir: &Path) -> anyhow::Result<impl Fn(&Path) -> bool + '_> {
+        let overrides = self.overrides(dir)?;
+        Ok(move |path: &Path| {
+            !path.starts_with(crate::cache::CACHE_DIR_NAME)
+                && !overrides.matched(path, false).is_ignore()
+                && self.has_source_name(path)
+        })
+    }
+
+    /// Whether a file of `size` bytes gets scanned: it fits --max-file-size
+    /// (which skips bundles and data dumps), or it is a bundle with a source
+    /// map in --bundles mode
+    pub fn accepts_size(&self, path: &Path, size: u64) -> bool {
+        size <= self.cli.max_file_size || (self.cli.bundles && crate::sourcemap::has_sibling_map(path))
+    }
+
+    /// Find all relevant source files in the directory
+    pub fn find_files(&self, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        for result in self.walk_builder(dir)?.build() {
             match result {
                 Ok(entry) => {
-                    if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                        if let Some(ext) = entry.path().extension() {
-                            if extensions.contains(&ext.to_str().unwrap_or("")) {
-                                files.push(entry.path().to_path_buf());
-                            }
-                        } else if entry.path().file_name()
-                            .and_then(|n| n.to_str())
-                            .map_or(false, |name| !name.contains('.')) {
-                            // Include files without extensions (scripts)
-                            files.push(entry.path().to_path_buf());
-                        }
+                    if self.is_source_file(&entry) {
+                        files.push(entry.into_path());
                     }
                 }
                 Err(e) => eprintln!("Warning: {}", e),
@@ -63,141 +138,1118 @@ impl FileScanner {
 
         Ok(files)
     }
+
+    /// Walk the directory on [`threads`](Self::threads) threads, handing every
+    /// source file to the visitor of the thread that found it.
+    ///
+    /// `make_visitor` is called once per thread, so per-thread state (such as a
+    /// channel sender) belongs in the visitor.
+    pub fn walk_parallel<'s, V>(&'s self, dir: &Path, mut make_visitor: impl FnMut() -> V) -> anyhow::Result<()>
+    where
+        V: FnMut(PathBuf) + Send + 's,
+    {
+        let walker = self.walk_builder(dir)?.threads(self.threads()).build_parallel();
+        walker.run(|| {
+            let mut visit = make_visitor();
+            Box::new(move |result| {
+                match result {
+                    Ok(entry) => {
+                        if self.is_source_file(&entry) {
+                            visit(entry.into_path());
+                        }
+                    }
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+                WalkState::Continue
+            })
+        });
+        Ok(())
+    }
 }
 
-/// Content scanner for finding endpoint usage in files
-pub struct ContentScanner {
-    endpoint_patterns: Vec<(crate::openapi::Endpoint, Regex)>,
+/// How a usage of an endpoint was detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MatchKind {
+    /// A path literal passed to an HTTP method call
+    Path,
+    /// A generated-client method named after the operation's `operationId`
+    OperationId,
 }
 
-impl ContentScanner {
-    pub fn new(endpoints: &[crate::openapi::Endpoint]) -> anyhow::Result<Self> {
-        let mut patterns = Vec::new();
+/// Where one usage of an endpoint was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchLocation {
+    pub file: String,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+    /// First line of the matched text
+    pub snippet: String,
+    /// Which detector fired (`call:template`, `regex:lowercase-method`, ...)
+    pub pattern: Cow<'static, str>,
+    pub kind: MatchKind,
+}
+
+/// An HTTP call whose path addresses no spec endpoint: a typo, or a call to
+/// an endpoint that was removed or never documented
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownCall {
+    pub method: crate::openapi::HttpMethod,
+    /// URL as written, with dynamic parts shown as `{*}`
+    pub url: String,
+    /// Lowercased host of an absolute URL
+    pub host: Option<String>,
+    pub location: MatchLocation,
+}
 
-        for endpoint in endpoints {
-            // Create regex patterns for this endpoint
-            let method_str = endpoint.method.as_str();
+impl UnknownCall {
+    /// `None` for URLs that can't be checked against the spec: fully dynamic
+    /// ones and relative file names
+    fn of(method: crate::openapi::HttpMethod, url: &UrlTemplate, location: MatchLocation) -> Option<Self> {
+        if !url.has_literal_path() {
+            return None;
+        }
+        Some(Self {
+            method,
+            url: url.to_string(),
+            host: url.host().map(|host| host.to_ascii_lowercase()),
+            location,
+        })
+    }
+}
 
-            // Pattern 1: Method calls like client.GET('/api/users')
-            let pattern1 = format!(r#"{}\s*\(\s*['"`](/[^'"`]*{})['"`]\s*\)"#,
-                                 regex::escape(method_str),
-                                 regex::escape(&endpoint.path));
+/// Aggregated usage of one endpoint across all scanned files
+#[derive(Debug, Clone, Default)]
+pub struct EndpointUsage {
+    pub match_count: usize,
+    pub files: Vec<String>,
+    /// Files where the endpoint was only detected through its operationId method name
+    pub operation_id_files: Vec<String>,
+    /// Every hit, ordered by file, line and column
+    pub locations: Vec<MatchLocation>,
+}
+
+/// Per-file matches: every hit for each endpoint
+pub type FileMatches = HashMap<crate::openapi::Endpoint, Vec<MatchLocation>>;
+
+/// One location per position: overlapping patterns firing on the same call
+/// count once, preferring path matches over operationId matches
+fn dedup_locations(matches: &mut FileMatches) {
+    for locations in matches.values_mut() {
+        // Bundle hits mapped back to their sources can span several files
+        locations.sort_by(|a, b| (&a.file, a.line, a.column, a.kind).cmp(&(&b.file, b.line, b.column, b.kind)));
+        locations.dedup_by(|a, b| (&a.file, a.line, a.column) == (&b.file, b.line, b.column));
+    }
+}
+
+/// Move bundle hits to their original source position. Hits without a mapping
+/// stay in the bundle; hits in `node_modules` sources are dropped unless the
+/// mode keeps them.
+fn remap_to_sources(matches: FileMatches, map: &SourceMap, mode: BundleMode) -> FileMatches {
+    let mut remapped = FileMatches::new();
+    for (endpoint, locations) in matches {
+        for location in locations {
+            if let Some(location) = remap_location(location, map, mode) {
+                remapped.entry(endpoint.clone()).or_default().push(location);
+            }
+        }
+    }
+    dedup_locations(&mut remapped);
+    remapped
+}
 
-            // Skip pattern 2 for now to avoid false positives
+fn remap_location(mut location: MatchLocation, map: &SourceMap, mode: BundleMode) -> Option<MatchLocation> {
+    if let Some(original) = map.lookup(location.line - 1, location.column - 1) {
+        if original.source.in_node_modules && !mode.include_node_modules {
+            return None;
+        }
+        location.file = original.source.path.clone();
+        location.line = original.line + 1;
+        location.column = original.column + 1;
+    }
+    Some(location)
+}
 
-            // Pattern 3: URL patterns with parameters
-            let param_pattern = convert_path_to_regex(&endpoint.path);
-            let pattern3 = format!(r#"{}\s*\(\s*['"`]({})['"`]\s*\)"#,
-                                 regex::escape(method_str),
-                                 param_pattern);
+/// Order unknown calls by position, keeping one per call
+fn sort_unknown(unknown: &mut Vec<UnknownCall>) {
+    unknown.sort_by(|a, b| {
+        let (a, b) = (&a.location, &b.location);
+        (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column))
+    });
+    unknown.dedup_by(|a, b| (&a.location.file, a.location.line, a.location.column) == (&b.location.file, b.location.line, b.location.column));
+}
 
-            // Pattern 4: Lowercase method calls like api.get('/api/users')
-            let lower_method = method_str.to_lowercase();
-            let pattern4 = format!(r#"{}\s*\(\s*['"`](/[^'"`]*{})['"`]\s*\)"#,
-                                 regex::escape(&lower_method),
-                                 regex::escape(&endpoint.path));
+/// URL argument of a parsed call as a path template, with constants resolved
+fn url_template(argument: &CallArgument, path: &Path, constants: &ConstantTable) -> UrlTemplate {
+    match argument {
+        CallArgument::Literal(url) => UrlTemplate::literal(url),
+        CallArgument::Template(template) => constants.resolve(path, template),
+        CallArgument::Identifier(name) => constants.resolve(path, &UrlTemplate::reference(name)),
+    }
+}
 
-            // Pattern 5: Lowercase with parameters
-            let pattern5 = format!(r#"{}\s*\(\s*['"`]({})['"`]\s*\)"#,
-                                 regex::escape(&lower_method),
-                                 param_pattern);
+/// Clients called with a URL and an options object (`fetch(url, { method })`,
+/// `axios({ method, url })`); the method defaults to GET
+const OPTIONS_CLIENTS: &[&str] = &["fetch", "$fetch", "ofetch", "ky", "axios", "request", "useFetch"];
 
-            // Pattern 6: More flexible method calls allowing for additional parameters
-            let pattern6 = format!(r#"{}\s*\(\s*['"`]({})['"`]"#,
-                                 regex::escape(&lower_method),
-                                 regex::escape(&endpoint.path));
+/// HTTP method and URL argument of a parsed call, with the label of the matching rule
+fn call_request<'c>(
+    call: &'c CallSite,
+    method_aliases: &HashMap<String, crate::openapi::HttpMethod>,
+) -> Option<(crate::openapi::HttpMethod, &'c CallArgument, &'static str)> {
+    // `api.get(url)`, `client.DELETE(url)`, or a configured alias (`api.remove(url)`)
+    let method = crate::openapi::HttpMethod::from_str(&call.callee)
+        .or_else(|| method_aliases.get(&call.callee).copied());
+    if let Some(method) = method {
+        let url = call.argument.as_ref()?;
+        let label = match url {
+            CallArgument::Literal(_) => "call:literal",
+            CallArgument::Template(_) => "call:template",
+            CallArgument::Identifier(_) => "call:constant",
+        };
+        return Some((method, url, label));
+    }
 
-            // Pattern 7: Uppercase with additional parameters
-            let pattern7 = format!(r#"{}\s*\(\s*['"`]({})['"`]"#,
-                                 regex::escape(method_str),
-                                 regex::escape(&endpoint.path));
+    if !OPTIONS_CLIENTS.contains(&call.callee.as_str()) {
+        return None;
+    }
+    let options = call.options.as_ref();
+    if options.is_some_and(|options| options.method_is_dynamic) {
+        return None;
+    }
+    let method = match options.and_then(|options| options.method.as_deref()) {
+        Some(name) => crate::openapi::HttpMethod::from_str(name)?,
+        None => crate::openapi::HttpMethod::Get,
+    };
+    let url = call.argument.as_ref().or_else(|| options.and_then(|options| options.url.as_ref()))?;
+    Some((method, url, "call:options"))
+}
 
+/// Outcome of the per-file pass of a scan
+#[derive(Default)]
+struct FilePass {
+    /// Matches that need nothing from other files
+    matches: FileMatches,
+    /// Calls of this file that need nothing from other files and address no endpoint
+    unknown: Vec<UnknownCall>,
+    /// What a parsed JS/TS file contributes to import resolution: its string
+    /// constants, imports, and the calls whose URLs may use imported constants.
+    /// The file content itself is not kept.
+    module: Option<SourceFile>,
+    /// Taken from the scan cache rather than scanned
+    cached: bool,
+}
 
+impl FilePass {
+    fn from_cache(entry: &CacheEntry) -> Self {
+        Self {
+            matches: entry.matches.iter().cloned().collect(),
+            unknown: entry.unknown.clone(),
+            module: entry.module.clone(),
+            cached: true,
+        }
+    }
+}
 
-            for pattern in [pattern1, pattern3, pattern4, pattern5, pattern6, pattern7] {
-                if let Ok(regex) = Regex::new(&pattern) {
-                    patterns.push((endpoint.clone(), regex));
+/// Whether a call's URL may use a constant imported from another file
+fn uses_imports(call: &CallSite, file: &SourceFile) -> bool {
+    if file.imports.is_empty() {
+        return false;
+    }
+    match &call.argument {
+        Some(CallArgument::Identifier(_)) => true,
+        Some(CallArgument::Template(template)) => template.has_references(),
+        _ => call.options.as_ref()
+            .and_then(|options| options.url.as_ref())
+            .is_some_and(|url| !matches!(url, CallArgument::Literal(_))),
+    }
+}
+
+/// Usage totals, folded in file by file as the scan progresses
+#[derive(Default)]
+struct UsageTotals {
+    usage: HashMap<crate::openapi::Endpoint, EndpointUsage>,
+}
+
+impl UsageTotals {
+    fn add(&mut self, matches: FileMatches) {
+        for (endpoint, locations) in matches {
+            self.usage.entry(endpoint).or_default().locations.extend(locations);
+        }
+    }
+
+    /// Derive the per-file lists from the locations; a file can contribute twice
+    /// (immediate and import-resolved matches)
+    fn finish(mut self) -> HashMap<crate::openapi::Endpoint, EndpointUsage> {
+        for usage in self.usage.values_mut() {
+            usage.locations.sort_by(|a, b| (&a.file, a.line, a.column, a.kind).cmp(&(&b.file, b.line, b.column, b.kind)));
+            usage.locations.dedup_by(|a, b| (&a.file, a.line, a.column) == (&b.file, b.line, b.column));
+            usage.match_count = usage.locations.len();
+
+            for location in &usage.locations {
+                if usage.files.last() != Some(&location.file) {
+                    usage.files.push(location.file.clone());
+                    usage.operation_id_files.push(location.file.clone());
+                }
+                if location.kind == MatchKind::Path && usage.operation_id_files.last() == Some(&location.file) {
+                    usage.operation_id_files.pop();
+                }
+            }
+        }
+        self.usage
+    }
+}
+
+/// Result of scanning a directory
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    pub usage: HashMap<crate::openapi::Endpoint, EndpointUsage>,
+    /// HTTP calls whose path addresses no spec endpoint, ordered by position
+    pub unknown: Vec<UnknownCall>,
+    pub files_scanned: usize,
+    /// Files whose results came from the scan cache
+    pub files_cached: usize,
+}
+
+/// Add `from` to `into`, keeping one location per position
+fn merge_matches(into: &mut FileMatches, from: FileMatches) {
+    for (endpoint, locations) in from {
+        into.entry(endpoint).or_default().extend(locations);
+    }
+    dedup_locations(into);
+}
+
+/// Shortest literal fragment worth prefiltering on; shorter ones match almost everywhere
+const MIN_FRAGMENT_LEN: usize = 3;
+
+/// Aho-Corasick prefilter over one literal fragment per entry.
+///
+/// A file (or URL) can only match an entry if the entry's fragment occurs in
+/// it, so one automaton pass replaces running every entry's regexes.
+#[derive(Clone)]
+struct Prefilter {
+    automaton: Option<Arc<AhoCorasick>>,
+    /// Entry index for each automaton pattern
+    owners: Vec<usize>,
+    /// Entries without a usable fragment, always candidates
+    always: Vec<usize>,
+    len: usize,
+}
+
+impl Prefilter {
+    fn new(fragments: &[Option<String>]) -> anyhow::Result<Self> {
+        let mut patterns = Vec::new();
+        let mut owners = Vec::new();
+        let mut always = Vec::new();
+
+        for (index, fragment) in fragments.iter().enumerate() {
+            match fragment {
+                Some(fragment) if fragment.len() >= MIN_FRAGMENT_LEN => {
+                    patterns.push(fragment.as_str());
+                    owners.push(index);
                 }
+                _ => always.push(index),
+            }
+        }
+
+        let automaton = if patterns.is_empty() {
+            None
+        } else {
+            Some(Arc::new(AhoCorasick::new(&patterns)?))
+        };
+
+        Ok(Self { automaton, owners, always, len: fragments.len() })
+    }
+
+    /// Indices of entries whose fragment occurs in `text`, in ascending order
+    fn candidates(&self, text: &str) -> Vec<usize> {
+        let mut hit = vec![false; self.len];
+        for &index in &self.always {
+            hit[index] = true;
+        }
+        if let Some(automaton) = &self.automaton {
+            for found in automaton.find_overlapping_iter(text) {
+                hit[self.owners[found.pattern().as_usize()]] = true;
             }
         }
 
+        hit.iter()
+            .enumerate()
+            .filter(|(_, hit)| **hit)
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Longest literal piece of a spec path (text outside `{param}`)
+fn literal_fragment(path: &str) -> Option<String> {
+    path.split(['{', '}'])
+        .step_by(2)
+        .map(|piece| piece.trim_end_matches('/'))
+        .max_by_key(|piece| piece.len())
+        .map(|piece| piece.to_string())
+}
+
+/// Everything needed to match one endpoint
+#[derive(Clone)]
+struct EndpointMatcher {
+    endpoint: crate::openapi::Endpoint,
+    /// Bare and base-path-prefixed paths
+    candidate_paths: Vec<String>,
+    /// Regex fallback patterns with their labels, compiled on first use
+    patterns: OnceCell<Vec<(&'static str, Regex)>>,
+}
+
+impl EndpointMatcher {
+    fn patterns(&self) -> &[(&'static str, Regex)] {
+        self.patterns.get_or_init(|| {
+            self.candidate_paths.iter()
+                .flat_map(|path| build_patterns(self.endpoint.method, path))
+                .collect()
+        })
+    }
+
+    /// Closest fit of a call URL among the bare and prefixed paths
+    fn specificity(&self, url: &UrlTemplate) -> Option<Specificity> {
+        self.candidate_paths.iter()
+            .filter_map(|path| url.specificity(path))
+            .reduce(|best, fit| if fit.beats(&best) { fit } else { best })
+    }
+}
+
+/// Content scanner for finding endpoint usage in files
+#[derive(Clone)]
+pub struct ContentScanner {
+    endpoints: Vec<EndpointMatcher>,
+    /// Prefilter over each endpoint's longest literal path fragment
+    path_prefilter: Prefilter,
+    /// Endpoint indices per HTTP method
+    by_method: HashMap<crate::openapi::HttpMethod, Vec<usize>>,
+    /// Member-call regexes for generated-client method names
+    operation_patterns: Vec<(crate::openapi::Endpoint, Regex)>,
+    /// Prefilter over the method name of each operation pattern
+    operation_prefilter: Prefilter,
+    /// Generated-client method names derived from operationIds
+    method_names: HashMap<String, Vec<crate::openapi::Endpoint>>,
+    /// Language-specific HTTP client detectors, selected by file extension
+    detectors: DetectorRegistry,
+    /// Project-specific patterns from `.epcheck.toml`, with their fixed method if any
+    custom_patterns: Vec<(Regex, Option<crate::openapi::HttpMethod>)>,
+    /// Wrapper method names that send a given HTTP method (`api.remove(url)`)
+    method_aliases: HashMap<String, crate::openapi::HttpMethod>,
+    /// Report hits in JS bundles at their original source via source maps
+    bundles: Option<BundleMode>,
+}
+
+impl ContentScanner {
+    /// Build usage patterns for each endpoint.
+    ///
+    /// `base_paths` are server/basePath prefixes (e.g. `/api/v2`); each endpoint
+    /// matches both its bare path and every prefixed form.
+    pub fn new(endpoints: &[crate::openapi::Endpoint], base_paths: &[String]) -> anyhow::Result<Self> {
+        let mut matchers = Vec::new();
+        let mut fragments = Vec::new();
+        let mut by_method: HashMap<crate::openapi::HttpMethod, Vec<usize>> = HashMap::new();
+
+        for (index, endpoint) in endpoints.iter().enumerate() {
+            let candidate_paths = std::iter::once(endpoint.path.clone())
+                .chain(base_paths.iter().map(|prefix| format!("{}{}", prefix, endpoint.path)))
+                .collect();
+
+            matchers.push(EndpointMatcher {
+                endpoint: endpoint.clone(),
+                candidate_paths,
+                patterns: OnceCell::new(),
+            });
+            fragments.push(literal_fragment(&endpoint.path));
+            by_method.entry(endpoint.method).or_default().push(index);
+        }
+
         Ok(Self {
-            endpoint_patterns: patterns,
+            endpoints: matchers,
+            path_prefilter: Prefilter::new(&fragments)?,
+            by_method,
+            operation_patterns: Vec::new(),
+            operation_prefilter: Prefilter::new(&[])?,
+            method_names: HashMap::new(),
+            detectors: DetectorRegistry::default(),
+            custom_patterns: Vec::new(),
+            method_aliases: HashMap::new(),
+            bundles: None,
         })
     }
 
-    /// Scan a file for endpoint usage
-    pub fn scan_file(&self, path: &Path) -> anyhow::Result<Vec<(crate::openapi::Endpoint, usize)>> {
-        let content = std::fs::read_to_string(path)?;
-        let mut found_endpoints = std::collections::HashMap::new();
+    /// Also detect calls to generated-client methods derived from each `operationId`
+    pub fn with_operation_ids(mut self, operation_ids: &[(crate::openapi::Endpoint, String)]) -> Self {
+        let mut fragments = Vec::new();
 
-        // Debug: print file content for files
-        if path.to_string_lossy().contains("traditional") || path.to_string_lossy().contains("userService") {
-            eprintln!("DEBUG: Scanning file: {}", path.display());
-            eprintln!("DEBUG: Content contains:");
-            for line in content.lines() {
-                if line.contains("api.") {
-                    eprintln!("  {}", line.trim());
+        for (endpoint, operation_id) in operation_ids {
+            for name in generated_method_names(operation_id) {
+                // Member calls only (`usersApi.getUserById(`), optionally with generic arguments
+                let pattern = format!(r#"\.\s*{}\s*(?:<[^()]*>)?\s*\("#, regex::escape(&name));
+                if let Ok(regex) = Regex::new(&pattern) {
+                    self.operation_patterns.push((endpoint.clone(), regex));
+                    fragments.push(Some(name.clone()));
                 }
+                self.method_names.entry(name).or_default().push(endpoint.clone());
             }
         }
 
-        for (endpoint, regex) in &self.endpoint_patterns {
-            let count = regex.find_iter(&content).count();
-            if count > 0 {
-                eprintln!("DEBUG: Found {} matches for {} {} in {}", count, endpoint.method.as_str(), endpoint.path, path.display());
-                *found_endpoints.entry(endpoint.clone()).or_insert(0) += count;
+        // Method names are plain identifiers, so building the automaton cannot fail
+        if let Ok(prefilter) = Prefilter::new(&fragments) {
+            self.operation_prefilter = prefilter;
+        }
+        self
+    }
+
+    /// Replace the default C#/Python/Go usage detectors
+    pub fn with_detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.detectors = detectors;
+        self
+    }
+
+    /// Add the custom patterns and method aliases of a project config.
+    ///
+    /// `{path}` in a pattern captures the URL and `{method}` the HTTP method
+    /// name or alias.
+    pub fn with_project_config(mut self, config: &crate::config::Config) -> anyhow::Result<Self> {
+        for (alias, method) in &config.method_aliases {
+            let method = crate::openapi::HttpMethod::from_str(method)
+                .ok_or_else(|| anyhow::anyhow!("Unknown HTTP method '{}' for method alias '{}'", method, alias))?;
+            self.method_aliases.insert(alias.clone(), method);
+        }
+
+        for custom in &config.patterns {
+            if !custom.pattern.contains("{path}") {
+                anyhow::bail!("Custom pattern '{}' has no {{path}} placeholder", custom.pattern);
             }
+            let method = match &custom.method {
+                Some(method) => Some(crate::openapi::HttpMethod::from_str(method)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown HTTP method '{}' in custom pattern '{}'", method, custom.pattern))?),
+                None if custom.pattern.contains("{method}") => None,
+                None => anyhow::bail!("Custom pattern '{}' needs a {{method}} placeholder or a method", custom.pattern),
+            };
+
+            let expanded = custom.pattern
+                .replace("{path}", r#"(?P<path>[^'"`\s,)]+)"#)
+                .replace("{method}", r"(?P<method>[A-Za-z_]+)");
+            let regex = Regex::new(&expanded)
+                .map_err(|e| anyhow::anyhow!("Invalid custom pattern '{}': {}", custom.pattern, e))?;
+            self.custom_patterns.push((regex, method));
         }
 
-        Ok(found_endpoints.into_iter().collect())
+        Ok(self)
     }
 
-    /// Scan multiple files concurrently and return detailed usage information
-    pub async fn scan_files(&self, files: Vec<PathBuf>) -> anyhow::Result<HashMap<crate::openapi::Endpoint, (usize, Vec<String>)>> {
-        let mut handles = Vec::new();
-        let patterns = &self.endpoint_patterns;
+    /// Treat JS files with a source map as bundles and report their hits in
+    /// the original sources
+    pub fn with_bundle_mode(mut self, mode: BundleMode) -> Self {
+        self.bundles = Some(mode);
+        self
+    }
 
-        for file in files {
-            let patterns_clone = patterns.clone();
-            let handle = task::spawn(async move {
-                let mut file_results = Vec::new();
-                if let Ok(content) = tokio::fs::read_to_string(&file).await {
-                        for (endpoint, regex) in &patterns_clone {
-                        let count = regex.find_iter(&content).count();
-                        if count > 0 {
-                            file_results.push((endpoint.clone(), count));
-                        }
+    /// Identifies everything that decides what a file matches: endpoints and
+    /// base paths, operationId methods, detectors, custom patterns, method
+    /// aliases and bundle mode. Cached scan results are only reused under the
+    /// same fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        for matcher in &self.endpoints {
+            matcher.endpoint.hash(&mut hasher);
+            matcher.candidate_paths.hash(&mut hasher);
+        }
+        for (endpoint, regex) in &self.operation_patterns {
+            endpoint.hash(&mut hasher);
+            regex.as_str().hash(&mut hasher);
+        }
+        self.detectors.extensions().hash(&mut hasher);
+        for (regex, method) in &self.custom_patterns {
+            regex.as_str().hash(&mut hasher);
+            method.hash(&mut hasher);
+        }
+        let mut aliases: Vec<_> = self.method_aliases.iter().collect();
+        aliases.sort_by_key(|(alias, _)| *alias);
+        aliases.hash(&mut hasher);
+        self.bundles.hash(&mut hasher);
+
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Literal text a usage of `endpoint` most likely contains: the longest
+    /// literal piece of its path and its generated-client method names. Empty
+    /// when the path has no distinctive literal text and no operationId.
+    pub fn search_terms(&self, endpoint: &crate::openapi::Endpoint) -> Vec<String> {
+        let mut terms: Vec<String> = literal_fragment(&endpoint.path)
+            .filter(|fragment| fragment.len() >= MIN_FRAGMENT_LEN)
+            .into_iter()
+            .collect();
+        for (name, endpoints) in &self.method_names {
+            if endpoints.contains(endpoint) {
+                terms.push(name.clone());
+            }
+        }
+        terms.sort();
+        terms
+    }
+
+    /// Find endpoint usage in the contents of one file.
+    ///
+    /// JavaScript/TypeScript files are parsed with tree-sitter so only real call
+    /// expressions count; languages with a registered [`UsageDetector`] use it,
+    /// and everything else (or unparsable files) uses the regexes. Custom
+    /// project patterns run on every file.
+    ///
+    /// Only constants declared in this file are resolved; use
+    /// [`scan_directory`](Self::scan_directory) to follow imports.
+    ///
+    /// [`UsageDetector`]: crate::detector::UsageDetector
+    pub fn scan_content(&self, path: &Path, content: &str) -> FileMatches {
+        let FilePass { mut matches, module, .. } = self.first_pass(path, content);
+        if let Some(module) = module {
+            merge_matches(&mut matches, self.match_module_locally(path, &module).0);
+        }
+        matches
+    }
+
+    /// Find endpoint usage in files given by content, resolving constants
+    /// imported from each other and from `imported`, whose own usage doesn't
+    /// count. Returns the matches of each file in `files`, in order.
+    pub fn scan_contents(&self, files: &[(PathBuf, String)], imported: &[(PathBuf, String)]) -> Vec<FileMatches> {
+        let mut constants = ConstantTable::default();
+        for (path, content) in imported {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if let Some(module) = crate::syntax::parse_source(content, extension) {
+                constants.add(path, &module);
+            }
+        }
+
+        let passes: Vec<FilePass> = files.iter().map(|(path, content)| self.first_pass(path, content)).collect();
+        for ((path, _), pass) in files.iter().zip(&passes) {
+            if let Some(module) = &pass.module {
+                constants.add(path, module);
+            }
+        }
+
+        files.iter()
+            .zip(passes)
+            .map(|((path, _), FilePass { mut matches, module, .. })| {
+                if let Some(module) = module {
+                    merge_matches(&mut matches, self.match_call_sites(path, &module.calls, &constants).0);
+                }
+                matches
+            })
+            .collect()
+    }
+
+    fn match_module_locally(&self, path: &Path, module: &SourceFile) -> (FileMatches, Vec<UnknownCall>) {
+        let mut constants = ConstantTable::default();
+        constants.add(path, module);
+        self.match_call_sites(path, &module.calls, &constants)
+    }
+
+    fn first_pass(&self, path: &Path, content: &str) -> FilePass {
+        // Bundles are self-contained: match everything now, then map each hit
+        // back to the source it was built from
+        let bundle = self.bundles.and_then(|mode| Some((mode, SourceMap::for_bundle(path, content)?)));
+        if let Some((mode, map)) = bundle {
+            let FilePass { mut matches, mut unknown, module, .. } = self.source_pass(path, content);
+            if let Some(module) = module {
+                let (module_matches, module_unknown) = self.match_module_locally(path, &module);
+                merge_matches(&mut matches, module_matches);
+                unknown.extend(module_unknown);
+            }
+            let mut unknown: Vec<UnknownCall> = unknown.into_iter()
+                .filter_map(|call| Some(UnknownCall { location: remap_location(call.location, &map, mode)?, ..call }))
+                .collect();
+            sort_unknown(&mut unknown);
+            return FilePass { matches: remap_to_sources(matches, &map, mode), unknown, module: None, cached: false };
+        }
+
+        self.source_pass(path, content)
+    }
+
+    fn source_pass(&self, path: &Path, content: &str) -> FilePass {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let (custom, mut unknown) = self.match_custom_patterns(path, content);
+
+        if let Some(mut file) = crate::syntax::parse_source(content, extension) {
+            // Calls resolvable within the file are matched now; only those that
+            // may use imported constants wait for the rest of the scan
+            let (deferred, ready): (Vec<CallSite>, Vec<CallSite>) = std::mem::take(&mut file.calls)
+                .into_iter()
+                .partition(|call| uses_imports(call, &file));
+
+            let mut local = ConstantTable::default();
+            local.add(path, &file);
+            let (mut matches, calls_unknown) = self.match_call_sites(path, &ready, &local);
+            merge_matches(&mut matches, custom);
+            unknown.extend(calls_unknown);
+
+            file.calls = deferred;
+            let module = if file.calls.is_empty() && file.constants.is_empty() { None } else { Some(file) };
+            return FilePass { matches, unknown, module, cached: false };
+        }
+
+        let mut matches = match self.detectors.for_extension(extension) {
+            Some(detector) => {
+                let (mut matches, detected_unknown) = self.match_detected_calls(path, &detector.detect(content));
+                unknown.extend(detected_unknown);
+                // Generated clients are still recognized by their operationId method names
+                merge_matches(&mut matches, self.match_patterns(path, content, Some(MatchKind::OperationId)));
+                matches
+            }
+            None => self.match_patterns(path, content, None),
+        };
+        merge_matches(&mut matches, custom);
+        FilePass { matches, unknown, module: None, cached: false }
+    }
+
+    fn match_custom_patterns(&self, path: &Path, content: &str) -> (FileMatches, Vec<UnknownCall>) {
+        if self.custom_patterns.is_empty() {
+            return (FileMatches::new(), Vec::new());
+        }
+
+        let mut calls = Vec::new();
+        let lines = LineIndex::new(content);
+        for (regex, fixed_method) in &self.custom_patterns {
+            for captures in regex.captures_iter(content) {
+                let (whole, url) = match (captures.get(0), captures.name("path")) {
+                    (Some(whole), Some(url)) => (whole, url),
+                    _ => continue,
+                };
+                let method = fixed_method.or_else(|| {
+                    captures.name("method").and_then(|name| self.method_for_name(name.as_str()))
+                });
+                let method = match method {
+                    Some(method) => method,
+                    None => continue,
+                };
+
+                let (line, column) = lines.position(content, whole.start());
+                calls.push(DetectedCall {
+                    method,
+                    url: UrlTemplate::literal(url.as_str()),
+                    line,
+                    column,
+                    snippet: snippet(whole.as_str()),
+                    pattern: "config:pattern",
+                });
+            }
+        }
+
+        self.match_detected_calls(path, &calls)
+    }
+
+    /// HTTP method for a called name: a method name itself or a configured alias
+    fn method_for_name(&self, name: &str) -> Option<crate::openapi::HttpMethod> {
+        crate::openapi::HttpMethod::from_str(name).or_else(|| self.method_aliases.get(name).copied())
+    }
+
+    /// Run the regex patterns, optionally only those of one kind
+    fn match_patterns(&self, path: &Path, content: &str, only: Option<MatchKind>) -> FileMatches {
+        let mut matches = FileMatches::new();
+        let file = path.to_string_lossy().to_string();
+        let lines = LineIndex::new(content);
+
+        let mut record = |endpoint: &crate::openapi::Endpoint, found: regex::Match, label, kind| {
+            let (line, column) = lines.position(content, found.start());
+            matches.entry(endpoint.clone()).or_default().push(MatchLocation {
+                file: file.clone(),
+                line,
+                column,
+                snippet: snippet(found.as_str()),
+                pattern: Cow::Borrowed(label),
+                kind,
+            });
+        };
+
+        // Only endpoints whose literal path fragment occurs in the file can match
+        if only != Some(MatchKind::OperationId) {
+            // `'/users/me'` also matches the `/users/{id}` patterns, so hits are
+            // grouped per call and only the most specific paths are kept
+            let mut by_position: HashMap<usize, Vec<(usize, regex::Match, &'static str, Specificity)>> = HashMap::new();
+            for index in self.path_prefilter.candidates(content) {
+                let matcher = &self.endpoints[index];
+                for (label, regex) in matcher.patterns() {
+                    for captures in regex.captures_iter(content) {
+                        let (found, url) = match (captures.get(0), captures.get(1)) {
+                            (Some(found), Some(url)) => (found, url),
+                            _ => continue,
+                        };
+                        let fit = matcher.specificity(&UrlTemplate::literal(url.as_str())).unwrap_or_default();
+                        by_position.entry(found.start()).or_default().push((index, found, *label, fit));
+                    }
+                }
+            }
+
+            for hits in by_position.values() {
+                for (index, found, label, fit) in hits {
+                    if !hits.iter().any(|(.., other)| other.beats(fit)) {
+                        record(&self.endpoints[*index].endpoint, *found, *label, MatchKind::Path);
                     }
                 }
-                (file, file_results)
+            }
+        }
+
+        if only != Some(MatchKind::Path) {
+            for index in self.operation_prefilter.candidates(content) {
+                let (endpoint, regex) = &self.operation_patterns[index];
+                for found in regex.find_iter(content) {
+                    record(endpoint, found, "regex:operation-id", MatchKind::OperationId);
+                }
+            }
+        }
+
+        dedup_locations(&mut matches);
+        matches
+    }
+
+    /// Match detected calls to endpoints; calls that address none are returned as unknown
+    fn match_detected_calls(&self, path: &Path, calls: &[DetectedCall]) -> (FileMatches, Vec<UnknownCall>) {
+        let mut matches = FileMatches::new();
+        let mut unknown = Vec::new();
+        let file = path.to_string_lossy().to_string();
+
+        for call in calls {
+            let location = MatchLocation {
+                file: file.clone(),
+                line: call.line,
+                column: call.column,
+                snippet: call.snippet.clone(),
+                pattern: Cow::Borrowed(call.pattern),
+                kind: MatchKind::Path,
+            };
+            let endpoints = self.endpoints_for_url(call.method, &call.url);
+            if endpoints.is_empty() {
+                unknown.extend(UnknownCall::of(call.method, &call.url, location));
+                continue;
+            }
+            for endpoint in endpoints {
+                matches.entry(endpoint.clone()).or_default().push(location.clone());
+            }
+        }
+
+        dedup_locations(&mut matches);
+        (matches, unknown)
+    }
+
+    /// Endpoints with this method whose path (bare or base-path prefixed) the
+    /// URL can address. Where paths overlap only the most specific count:
+    /// `/users/me` rather than `/users/{id}` for a call to `/users/me`.
+    fn endpoints_for_url(&self, method: crate::openapi::HttpMethod, url: &UrlTemplate) -> Vec<&crate::openapi::Endpoint> {
+        let indices = match self.by_method.get(&method) {
+            Some(indices) => indices,
+            None => return Vec::new(),
+        };
+
+        // A plain literal URL contains the literal fragment of every path it can address
+        let candidates = url.as_literal().map(|literal| self.path_prefilter.candidates(literal));
+
+        let matched: Vec<(&crate::openapi::Endpoint, Specificity)> = indices.iter()
+            .filter(|&&index| candidates.as_ref().is_none_or(|c| c.binary_search(&index).is_ok()))
+            .map(|&index| &self.endpoints[index])
+            .filter_map(|matcher| Some((&matcher.endpoint, matcher.specificity(url)?)))
+            .collect();
+
+        matched.iter()
+            .filter(|(_, fit)| !matched.iter().any(|(_, other)| other.beats(fit)))
+            .map(|(endpoint, _)| *endpoint)
+            .collect()
+    }
+
+    /// Match parsed calls to endpoints; HTTP calls that address none are returned as unknown
+    fn match_call_sites(
+        &self,
+        path: &Path,
+        calls: &[CallSite],
+        constants: &ConstantTable,
+    ) -> (FileMatches, Vec<UnknownCall>) {
+        let mut matches = FileMatches::new();
+        let mut unknown = Vec::new();
+        let file = path.to_string_lossy().to_string();
+
+        for call in calls {
+            let mut hits: Vec<(&crate::openapi::Endpoint, MatchKind, &'static str)> = Vec::new();
+
+            if let Some((method, argument, label)) = call_request(call, &self.method_aliases) {
+                let url = url_template(argument, path, constants);
+                let endpoints = self.endpoints_for_url(method, &url);
+                if endpoints.is_empty() {
+                    let location = MatchLocation {
+                        file: file.clone(),
+                        line: call.line,
+                        column: call.column,
+                        snippet: call.snippet.clone(),
+                        pattern: Cow::Borrowed(label),
+                        kind: MatchKind::Path,
+                    };
+                    unknown.extend(UnknownCall::of(method, &url, location));
+                }
+                for endpoint in endpoints {
+                    hits.push((endpoint, MatchKind::Path, label));
+                }
+            }
+
+            // Generated clients are always called through an object, like the regex path requires
+            if let Some(endpoints) = self.method_names.get(&call.callee).filter(|_| call.member) {
+                for endpoint in endpoints {
+                    hits.push((endpoint, MatchKind::OperationId, "call:operation-id"));
+                }
+            }
+
+            for (endpoint, kind, label) in hits {
+                matches.entry(endpoint.clone()).or_default().push(MatchLocation {
+                    file: file.clone(),
+                    line: call.line,
+                    column: call.column,
+                    snippet: call.snippet.clone(),
+                    pattern: Cow::Borrowed(label),
+                    kind,
+                });
+            }
+        }
+
+        // A call counts once per endpoint even if several base-path variants match
+        dedup_locations(&mut matches);
+        (matches, unknown)
+    }
+
+    /// Scan every source file under `dir` and return detailed usage information.
+    ///
+    /// Paths stream from the parallel walker straight into its worker threads,
+    /// which read and scan each file; results go through a bounded channel and
+    /// are folded into the totals as they arrive. Peak memory therefore depends
+    /// on the thread count and the number of matches, not on repository size.
+    /// Calls that use imported constants are matched at the end, once every
+    /// file's constants are known.
+    ///
+    /// With a cache, unchanged files reuse their stored first-pass results;
+    /// each file's entry for the next run goes to `writer` as it arrives.
+    pub fn scan_directory(
+        &self,
+        files: &FileScanner,
+        dir: &Path,
+        cache: Option<&ScanCache>,
+        mut writer: Option<&mut CacheWriter>,
+    ) -> anyhow::Result<ScanSummary> {
+        let (sender, receiver) = mpsc::sync_channel::<(PathBuf, FilePass, Option<CacheEntry>)>(files.threads() * 2);
+
+        std::thread::scope(|scope| {
+            let walker = scope.spawn(move || {
+                files.walk_parallel(dir, || {
+                    let sender = sender.clone();
+                    move |path: PathBuf| {
+                        let (pass, entry) = self.cached_pass(&path, cache);
+                        let _ = sender.send((path, pass, entry));
+                    }
+                })
             });
-            handles.push(handle);
+
+            let mut totals = UsageTotals::default();
+            let mut files_scanned = 0;
+            let mut files_cached = 0;
+            let mut unknown = Vec::new();
+            let mut modules = Vec::new();
+            let mut constants = ConstantTable::default();
+
+            for (path, FilePass { matches, unknown: file_unknown, module, cached }, entry) in receiver {
+                files_scanned += 1;
+                files_cached += cached as usize;
+                if let (Some(writer), Some(entry)) = (writer.as_deref_mut(), entry) {
+                    writer.write(&entry);
+                }
+                totals.add(matches);
+                unknown.extend(file_unknown);
+                if let Some(module) = module {
+                    constants.add(&path, &module);
+                    if !module.calls.is_empty() {
+                        modules.push((path, module.calls));
+                    }
+                }
+            }
+            walker.join().map_err(|_| anyhow::anyhow!("File walker thread panicked"))??;
+
+            for (path, calls) in modules {
+                let (matches, calls_unknown) = self.match_call_sites(&path, &calls, &constants);
+                totals.add(matches);
+                unknown.extend(calls_unknown);
+            }
+            sort_unknown(&mut unknown);
+
+            Ok(ScanSummary { usage: totals.finish(), unknown, files_scanned, files_cached })
+        })
+    }
+
+    /// First pass over one file, reused from the cache when the file is unchanged.
+    ///
+    /// Unreadable and non-UTF-8 files count as scanned without matches. When
+    /// caching, also returns the file's entry for the updated cache.
+    fn cached_pass(&self, path: &Path, cache: Option<&ScanCache>) -> (FilePass, Option<CacheEntry>) {
+        let cache = match cache {
+            Some(cache) => cache,
+            None => {
+                let pass = match std::fs::read_to_string(path) {
+                    Ok(content) => self.first_pass(path, &content),
+                    Err(_) => FilePass::default(),
+                };
+                return (pass, None);
+            }
+        };
+
+        let stamp = match std::fs::metadata(path) {
+            Ok(metadata) => FileStamp::of(&metadata),
+            Err(_) => return (FilePass::default(), None),
+        };
+        let map_stamp = self.map_stamp(path);
+        if let Some(entry) = cache.get(path, stamp, map_stamp) {
+            return (FilePass::from_cache(entry), Some(entry.clone()));
+        }
+
+        let content = match std::fs::read(path) {
+            Ok(content) => content,
+            Err(_) => return (FilePass::default(), None),
+        };
+        let hash = content_hash(&content);
+        // Touched but unchanged (checkout, formatter run without edits)
+        if let Some(entry) = cache.get_by_hash(path, hash, map_stamp) {
+            return (FilePass::from_cache(entry), Some(CacheEntry { stamp, ..entry.clone() }));
+        }
+
+        let pass = match std::str::from_utf8(&content) {
+            Ok(content) => self.first_pass(path, content),
+            Err(_) => FilePass::default(),
+        };
+        let entry = CacheEntry {
+            path: path.to_path_buf(),
+            stamp,
+            hash,
+            map_stamp,
+            matches: pass.matches.iter().map(|(endpoint, locations)| (endpoint.clone(), locations.clone())).collect(),
+            unknown: pass.unknown.clone(),
+            module: pass.module.clone(),
+        };
+        (pass, Some(entry))
+    }
+
+    /// Stamp of the source map next to a bundle in `--bundles` mode
+    fn map_stamp(&self, path: &Path) -> Option<FileStamp> {
+        if self.bundles.is_none() || !crate::sourcemap::has_sibling_map(path) {
+            return None;
         }
+        std::fs::metadata(crate::sourcemap::sibling_map(path)).ok().map(|metadata| FileStamp::of(&metadata))
+    }
+}
+
+/// Build the usage regexes for one method and one concrete path
+fn build_patterns(method: crate::openapi::HttpMethod, path: &str) -> Vec<(&'static str, Regex)> {
+    // Create regex patterns for this endpoint
+    let method_str = method.as_str();
+
+    // Pattern 1: Method calls like client.GET('/api/users')
+    let pattern1 = format!(r#"{}\s*\(\s*['"`](/[^'"`]*{})['"`]\s*\)"#,
+                         regex::escape(method_str),
+                         regex::escape(path));
+
+    // Skip pattern 2 for now to avoid false positives
+
+    // Pattern 3: URL patterns with parameters
+    let param_pattern = convert_path_to_regex(path);
+    let pattern3 = format!(r#"{}\s*\(\s*['"`]({})['"`]\s*\)"#,
+                         regex::escape(method_str),
+                         param_pattern);
+
+    // Pattern 4: Lowercase method calls like api.get('/api/users')
+    let lower_method = method_str.to_lowercase();
+    let pattern4 = format!(r#"{}\s*\(\s*['"`](/[^'"`]*{})['"`]\s*\)"#,
+                         regex::escape(&lower_method),
+                         regex::escape(path));
+
+    // Pattern 5: Lowercase with parameters
+    let pattern5 = format!(r#"{}\s*\(\s*['"`]({})['"`]\s*\)"#,
+                         regex::escape(&lower_method),
+                         param_pattern);
 
-        let mut all_results: HashMap<crate::openapi::Endpoint, (usize, std::collections::HashSet<String>)> = HashMap::new();
+    // Pattern 6: More flexible method calls allowing for additional parameters
+    let pattern6 = format!(r#"{}\s*\(\s*['"`]({})['"`]"#,
+                         regex::escape(&lower_method),
+                         regex::escape(path));
 
-        for handle in handles {
-            let (file_path, file_results) = handle.await?;
-            let file_name = file_path.to_string_lossy().to_string();
+    // Pattern 7: Uppercase with additional parameters
+    let pattern7 = format!(r#"{}\s*\(\s*['"`]({})['"`]"#,
+                         regex::escape(method_str),
+                         regex::escape(path));
 
-            for (endpoint, count) in file_results {
-                let (total_count, files) = all_results.entry(endpoint).or_insert((0, std::collections::HashSet::new()));
-                *total_count += count;
-                files.insert(file_name.clone());
+    [
+        ("regex:method", pattern1),
+        ("regex:method-params", pattern3),
+        ("regex:lowercase-method", pattern4),
+        ("regex:lowercase-method-params", pattern5),
+        ("regex:lowercase-method-args", pattern6),
+        ("regex:method-args", pattern7),
+    ]
+        .into_iter()
+        .filter_map(|(label, pattern)| Regex::new(&pattern).ok().map(|regex| (label, regex)))
+        .collect()
+}
+
+/// Derive the method names code generators emit for an `operationId`.
+///
+/// Covers camelCase (TypeScript/Java), PascalCase plus the `Async` suffix (C#)
+/// and snake_case (Python), alongside the operationId itself when it is
+/// already a valid identifier. Single-word operationIds (`list`, `search`,
+/// `get`) are too generic to tell a generated client apart and give none.
+fn generated_method_names(operation_id: &str) -> Vec<String> {
+    let words = split_words(operation_id);
+    if words.len() < 2 {
+        return Vec::new();
+    }
+
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let pascal: String = lower.iter().map(|w| capitalize(w)).collect();
+    let camel = format!("{}{}", lower[0], lower[1..].iter().map(|w| capitalize(w)).collect::<String>());
+    let snake = lower.join("_");
+
+    let mut names = vec![camel, pascal.clone(), format!("{}Async", pascal), snake];
+    if operation_id.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+        names.push(operation_id.to_string());
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Split an identifier into words on separators and camelCase boundaries
+fn split_words(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
             }
+            continue;
         }
 
-        // Convert HashSet to Vec for the final result
-        let mut final_results: HashMap<crate::openapi::Endpoint, (usize, Vec<String>)> = HashMap::new();
-        for (endpoint, (total_count, files_set)) in all_results {
-            let mut files_vec: Vec<String> = files_set.into_iter().collect();
-            files_vec.sort(); // Sort for consistent output
-            final_results.insert(endpoint, (total_count, files_vec));
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // "getUser" -> get|User, "HTTPStatus" -> HTTP|Status, "v2Users" -> v2|Users
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
         }
+        current.push(c);
+    }
 
-        Ok(final_results)
+    if !current.is_empty() {
+        words.push(current);
     }
+    words
 }
 
 /// Convert OpenAPI path with parameters to regex pattern
@@ -208,4 +1260,21 @@ fn convert_path_to_regex(path: &str) -> String {
     // Convert {param} to [^/]+ (one or more non-slash characters)
     let param_regex = Regex::new(r"\\\{[^}]+\}").unwrap();
     param_regex.replace_all(&escaped, r"[^/]+").to_string()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::{Endpoint, HttpMethod};
+
+    #[test]
+    fn base_paths_prefix_candidate_paths() {
+        let endpoint = Endpoint::new("/users/{id}".to_string(), HttpMethod::Get);
+        let scanner = ContentScanner::new(&[endpoint], &["/api/v2".to_string()]).unwrap();
+
+        let matcher = &scanner.endpoints[0];
+        assert_eq!(matcher.candidate_paths, ["/users/{id}", "/api/v2/users/{id}"]);
+        assert!(matcher.specificity(&UrlTemplate::literal("/api/v2/users/42")).is_some());
+        assert!(matcher.specificity(&UrlTemplate::literal("/users/42")).is_some());
+        assert!(matcher.specificity(&UrlTemplate::literal("/api/v1/users/42")).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Extensions of JavaScript files that may be built bundles
+const BUNDLE_EXTENSIONS: &[&str] = &["js", "mjs", "cjs"];
+
+/// How hits in built bundles are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BundleMode {
+    /// Keep hits whose original source is a dependency under `node_modules`
+    pub include_node_modules: bool,
+}
+
+/// Position in an original source file, 0-based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OriginalPosition<'m> {
+    pub source: &'m MappedSource,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedSource {
+    /// Source path as reported: relative to the scanned tree where possible
+    pub path: String,
+    pub in_node_modules: bool,
+}
+
+/// One mapping from a generated column to an original position
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    generated_column: u32,
+    source: u32,
+    line: u32,
+    column: u32,
+}
+
+/// Whether `path` is a JS file with a `<bundle>.map` source map next to it
+pub fn has_sibling_map(path: &Path) -> bool {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    BUNDLE_EXTENSIONS.contains(&extension) && sibling_map(path).is_file()
+}
+
+pub(crate) fn sibling_map(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.map", path.display()))
+}
+
+/// A decoded version 3 source map
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<MappedSource>,
+    /// Segments of each generated line, ordered by generated column
+    lines: Vec<Vec<Segment>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Source map of a JS bundle: the one named by its `sourceMappingURL`
+    /// comment (a file or an inline `data:` URL), else `<bundle>.map` next to it
+    pub fn for_bundle(path: &Path, content: &str) -> Option<Self> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        if !BUNDLE_EXTENSIONS.contains(&extension) {
+            return None;
+        }
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        if let Some(url) = mapping_url(content) {
+            if let Some(data) = url.strip_prefix("data:") {
+                let (_, encoded) = data.split_once(";base64,")?;
+                return Self::parse(&decode_base64(encoded)?, dir);
+            }
+            let url = url.split(['?', '#']).next().unwrap_or(url);
+            let map_path = dir.join(url);
+            let map_dir = map_path.parent().unwrap_or(dir).to_path_buf();
+            return Self::parse(&std::fs::read(&map_path).ok()?, &map_dir);
+        }
+
+        Self::parse(&std::fs::read(sibling_map(path)).ok()?, dir)
+    }
+
+    /// Parse a source map; relative sources are resolved against `map_dir`.
+    /// Index maps (`sections`) are not supported.
+    pub fn parse(json: &[u8], map_dir: &Path) -> Option<Self> {
+        let raw: RawSourceMap = serde_json::from_slice(json).ok()?;
+        if raw.version != 3 {
+            return None;
+        }
+
+        let sources = raw.sources.iter()
+            .map(|source| {
+                let path = source_path(source.as_deref().unwrap_or(""), raw.source_root.as_deref(), map_dir);
+                let in_node_modules = Path::new(&path).components()
+                    .any(|component| component.as_os_str() == "node_modules");
+                MappedSource { path, in_node_modules }
+            })
+            .collect();
+
+        Some(Self { sources, lines: decode_mappings(&raw.mappings)? })
+    }
+
+    /// Original position of a 0-based generated line and column: the closest
+    /// mapping at or before the column on that line
+    pub fn lookup(&self, line: usize, column: usize) -> Option<OriginalPosition<'_>> {
+        let segments = self.lines.get(line)?;
+        let index = segments.partition_point(|segment| segment.generated_column as usize <= column);
+        let segment = segments.get(index.checked_sub(1)?)?;
+        Some(OriginalPosition {
+            source: self.sources.get(segment.source as usize)?,
+            line: segment.line as usize,
+            column: segment.column as usize,
+        })
+    }
+}
+
+/// URL from the last `//# sourceMappingURL=` comment
+fn mapping_url(content: &str) -> Option<&str> {
+    let (start, marker) = ["//# sourceMappingURL=", "//@ sourceMappingURL="].iter()
+        .filter_map(|marker| content.rfind(marker).map(|start| (start, marker)))
+        .max_by_key(|(start, _)| *start)?;
+    let url = content[start + marker.len()..].split_whitespace().next()?;
+    Some(url)
+}
+
+/// Reported path of a source: bundler URLs (`webpack://app/./src/api.ts`)
+/// lose their scheme and namespace, relative paths are resolved against the
+/// map's directory
+fn source_path(source: &str, source_root: Option<&str>, map_dir: &Path) -> String {
+    let joined = match source_root.filter(|root| !root.is_empty() && !source.contains("://")) {
+        Some(root) => format!("{}/{}", root.trim_end_matches('/'), source),
+        None => source.to_string(),
+    };
+
+    if let Some(path) = joined.strip_prefix("file://") {
+        return path.to_string();
+    }
+    if let Some((_, rest)) = joined.split_once("://") {
+        let without_namespace = rest.split_once('/').map_or(rest, |(_, path)| path);
+        return without_namespace.trim_start_matches('/').trim_start_matches("./").to_string();
+    }
+    if Path::new(&joined).is_absolute() {
+        return joined;
+    }
+
+    crate::paths::normalize(&map_dir.join(joined)).to_string_lossy().to_string()
+}
+
+/// Decode the `mappings` field: `;` separates generated lines, `,` segments,
+/// and each segment is base64 VLQ fields relative to the previous segment
+fn decode_mappings(mappings: &str) -> Option<Vec<Vec<Segment>>> {
+    let mut lines = Vec::new();
+    let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+
+    for encoded_line in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+
+        for encoded in encoded_line.split(',').filter(|segment| !segment.is_empty()) {
+            let fields = decode_vlq(encoded)?;
+            generated_column += *fields.first()?;
+            // One-field segments map generated code to no original position
+            if fields.len() < 4 {
+                continue;
+            }
+            source += fields[1];
+            line += fields[2];
+            column += fields[3];
+
+            segments.push(Segment {
+                generated_column: u32::try_from(generated_column).ok()?,
+                source: u32::try_from(source).ok()?,
+                line: u32::try_from(line).ok()?,
+                column: u32::try_from(column).ok()?,
+            });
+        }
+
+        segments.sort_by_key(|segment| segment.generated_column);
+        lines.push(segments);
+    }
+
+    Some(lines)
+}
+
+fn decode_vlq(encoded: &str) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+
+    for byte in encoded.bytes() {
+        let digit = base64_value(byte)? as i64;
+        value += (digit & 0b11111) << shift;
+        if digit & 0b100000 != 0 {
+            shift += 5;
+            if shift > 60 {
+                return None;
+            }
+        } else {
+            let magnitude = value >> 1;
+            values.push(if value & 1 == 1 { -magnitude } else { magnitude });
+            value = 0;
+            shift = 0;
+        }
+    }
+
+    // A trailing continuation digit leaves a value unfinished
+    (shift == 0).then_some(values)
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+
+    for byte in encoded.bytes().filter(|&byte| byte != b'=') {
+        buffer = (buffer << 6) | base64_value(byte)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(generated column, source, line, column)` of each segment, per line
+    fn positions(mappings: &str) -> Vec<Vec<(u32, u32, u32, u32)>> {
+        decode_mappings(mappings).unwrap().iter()
+            .map(|line| line.iter().map(|s| (s.generated_column, s.source, s.line, s.column)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn vlq_values() {
+        assert_eq!(decode_vlq("AACDEF"), Some(vec![0, 0, 1, -1, 2, -2]));
+        assert_eq!(decode_vlq("gBhB2H"), Some(vec![16, -16, 123]));
+        assert_eq!(decode_vlq("AAg"), None);
+        assert_eq!(decode_vlq("A!"), None);
+    }
+
+    #[test]
+    fn base64_payloads() {
+        assert_eq!(decode_base64("eyJ2IjozfQ==").unwrap(), br#"{"v":3}"#);
+        // URL-safe alphabet
+        assert_eq!(decode_base64("-_8").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("e y"), None);
+    }
+
+    #[test]
+    fn mappings_are_relative_to_the_previous_segment() {
+        assert_eq!(positions("AAAA,GAAG,EACD;AAAM,ECAA;;C,ADDD;EAAA,FAAA"), [
+            vec![(0, 0, 0, 0), (3, 0, 0, 3), (5, 0, 1, 2)],
+            // The generated column restarts on each line, the other fields carry over
+            vec![(0, 0, 1, 8), (2, 1, 1, 8)],
+            vec![],
+            // One-field segments map to nothing; `D` steps source, line and column back by one
+            vec![(1, 0, 0, 7)],
+            // Negative generated deltas are put back in column order
+            vec![(0, 0, 0, 7), (2, 0, 0, 7)],
+        ]);
+        assert!(decode_mappings("AAAA,AADA").is_none());
+    }
+
+    #[test]
+    fn lookup_finds_the_closest_preceding_segment() {
+        let map = SourceMap::parse(br#"{"version": 3, "sources": ["webpack://app/./src/api.ts", "../node_modules/x/index.js"], "mappings": "AAAA,KCCE"}"#, Path::new("dist")).unwrap();
+
+        assert_eq!(map.lookup(0, 3).map(|p| (p.source.path.as_str(), p.line, p.column)), Some(("src/api.ts", 0, 0)));
+        let dependency = map.lookup(0, 9).unwrap();
+        assert_eq!((dependency.source.path.as_str(), dependency.line, dependency.column), ("node_modules/x/index.js", 1, 2));
+        assert!(dependency.source.in_node_modules);
+        assert!(map.lookup(1, 0).is_none());
+    }
+
+    #[test]
+    fn inline_maps_are_read_from_the_bundle() {
+        let bundle = "fetch('/users');\n//# sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbIi4uL3NyYy9hcGkudHMiXSwibWFwcGluZ3MiOiJBQUFBLEVBQUUifQ==\n";
+        let map = SourceMap::for_bundle(Path::new("dist/app.js"), bundle).unwrap();
+        let position = map.lookup(0, 4).unwrap();
+        assert_eq!((position.source.path.as_str(), position.column), ("src/api.ts", 2));
+
+        assert!(SourceMap::for_bundle(Path::new("src/app.ts"), bundle).is_none());
+    }
+}
@@ -0,0 +1,491 @@
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Parser};
+
+use crate::template::{UrlPart, UrlTemplate};
+
+/// First argument of a call expression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallArgument {
+    /// A plain string, or a template literal without substitutions
+    Literal(String),
+    /// A template literal with `${...}` substitutions, or a `+` concatenation
+    /// involving at least one string
+    Template(UrlTemplate),
+    /// A bare identifier such as a constant holding the URL (`USERS_URL`), or a
+    /// member of a namespace import (`routes.USERS_URL`)
+    Identifier(String),
+}
+
+/// An imported binding: `import { imported as local } from 'source'`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Import {
+    pub local: String,
+    /// Exported name in the source module, or `*` for namespace imports
+    pub imported: String,
+    /// Module specifier as written (`./routes`, `@/api/routes`)
+    pub source: String,
+}
+
+/// What the scanner needs from one parsed JavaScript/TypeScript file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub calls: Vec<CallSite>,
+    /// `const` declarations with string values, in source order. Values may
+    /// still contain references to other constants.
+    pub constants: Vec<(String, UrlTemplate)>,
+    pub imports: Vec<Import>,
+}
+
+/// `method` and `url` read from a request options object, as in
+/// `fetch('/users', { method: 'DELETE' })` or `axios({ method: 'put', url: '/users/1' })`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestOptions {
+    /// String literal `method` value
+    pub method: Option<String>,
+    /// `method` is set, but not to a string literal (`{ method }`, `{ method: verb }`),
+    /// or the options are not an object literal (`fetch(url, init)`)
+    pub method_is_dynamic: bool,
+    pub url: Option<CallArgument>,
+}
+
+/// A call expression found in JavaScript/TypeScript source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSite {
+    /// Called function or method name (`get` for `api.get(...)`, `fetch` for `fetch(...)`)
+    pub callee: String,
+    /// Whether the callee is a method of some object rather than a bare function
+    pub member: bool,
+    pub argument: Option<CallArgument>,
+    /// Object literal passed as the first or second argument
+    pub options: Option<RequestOptions>,
+    /// 1-based line of the call
+    pub line: usize,
+    /// 1-based column of the call
+    pub column: usize,
+    /// First line of the call expression
+    pub snippet: String,
+}
+
+/// Tree-sitter grammar for a JavaScript-family file extension
+fn language_for(extension: &str) -> Option<Language> {
+    match extension {
+        "js" | "jsx" | "mjs" | "cjs" => Some(tree_sitter_javascript::language()),
+        "ts" | "mts" | "cts" => Some(tree_sitter_typescript::language_typescript()),
+        "tsx" => Some(tree_sitter_typescript::language_tsx()),
+        _ => None,
+    }
+}
+
+/// Whether files with this extension are analyzed syntactically
+pub fn is_supported(extension: &str) -> bool {
+    language_for(extension).is_some()
+}
+
+/// Parse a JS/TS/JSX/TSX source file and collect its calls, string constants
+/// and imports.
+///
+/// Comments and strings are never mistaken for calls, and calls split across
+/// lines or carrying type arguments (`api.get<User>('/users')`) are handled.
+/// Returns `None` for unsupported extensions or when parsing fails, so callers
+/// can fall back to regex scanning.
+pub fn parse_source(source: &str, extension: &str) -> Option<SourceFile> {
+    let language = language_for(extension)?;
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let bytes = source.as_bytes();
+    let mut file = SourceFile::default();
+    let mut constants = Vec::new();
+    let mut stack = vec![tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            "call_expression" => {
+                if let Some(call) = call_site(node, bytes) {
+                    file.calls.push(call);
+                }
+            }
+            "lexical_declaration" => constants.extend(const_declarations(node, bytes)),
+            "import_statement" => file.imports.extend(imports(node, bytes)),
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    file.calls.sort_by_key(|c| (c.line, c.column));
+    constants.sort_by_key(|(start, _, _)| *start);
+    file.constants = constants.into_iter().map(|(_, name, value)| (name, value)).collect();
+    Some(file)
+}
+
+/// `const NAME = <string expression>` declarators, with their start byte
+fn const_declarations(node: Node, source: &[u8]) -> Vec<(usize, String, UrlTemplate)> {
+    let is_const = node.child(0)
+        .and_then(|keyword| keyword.utf8_text(source).ok())
+        == Some("const");
+    if !is_const {
+        return Vec::new();
+    }
+
+    let mut declarations = Vec::new();
+    let mut cursor = node.walk();
+    for declarator in node.named_children(&mut cursor) {
+        if declarator.kind() != "variable_declarator" {
+            continue;
+        }
+        let name = match declarator.child_by_field_name("name") {
+            Some(name) if name.kind() == "identifier" => name,
+            _ => continue,
+        };
+        let value = match declarator.child_by_field_name("value").and_then(|value| string_value(value, source)) {
+            Some(value) => value,
+            None => continue,
+        };
+        if let Ok(name) = name.utf8_text(source) {
+            declarations.push((declarator.start_byte(), name.to_string(), value));
+        }
+    }
+    declarations
+}
+
+/// Template for an expression that evaluates to a string, if it plausibly does
+fn string_value(node: Node, source: &[u8]) -> Option<UrlTemplate> {
+    match node.kind() {
+        "string" => {
+            let text = node.utf8_text(source).ok()?;
+            Some(UrlTemplate::literal(strip_delimiters(text)))
+        }
+        "template_string" => Some(UrlTemplate::new(template_parts(node, source))),
+        "binary_expression" => {
+            let mut parts = Vec::new();
+            if !concatenation_parts(node, source, &mut parts) {
+                return None;
+            }
+            Some(UrlTemplate::new(parts))
+        }
+        // `'/api/users' as const`
+        "as_expression" | "satisfies_expression" | "parenthesized_expression" => {
+            let inner = node.named_child(0)?;
+            string_value(inner, source)
+        }
+        _ => None,
+    }
+}
+
+/// Bindings introduced by an `import ... from '...'` statement
+fn imports(node: Node, source: &[u8]) -> Vec<Import> {
+    let specifier = match node.child_by_field_name("source").and_then(|s| s.utf8_text(source).ok()) {
+        Some(text) => strip_delimiters(text).to_string(),
+        None => return Vec::new(),
+    };
+
+    let mut found = Vec::new();
+    let mut add = |local: Node, imported: &str| {
+        if let Ok(local) = local.utf8_text(source) {
+            found.push(Import {
+                local: local.to_string(),
+                imported: imported.to_string(),
+                source: specifier.clone(),
+            });
+        }
+    };
+
+    let mut cursor = node.walk();
+    for clause in node.named_children(&mut cursor).filter(|child| child.kind() == "import_clause") {
+        let mut clause_cursor = clause.walk();
+        for binding in clause.named_children(&mut clause_cursor) {
+            match binding.kind() {
+                "identifier" => add(binding, "default"),
+                "namespace_import" => {
+                    if let Some(local) = binding.named_child(0) {
+                        add(local, "*");
+                    }
+                }
+                "named_imports" => {
+                    let mut named_cursor = binding.walk();
+                    for spec in binding.named_children(&mut named_cursor) {
+                        let name = match spec.child_by_field_name("name") {
+                            Some(name) => name,
+                            None => continue,
+                        };
+                        let local = spec.child_by_field_name("alias").unwrap_or(name);
+                        if let Ok(imported) = name.utf8_text(source) {
+                            add(local, imported);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    found
+}
+
+fn call_site(node: Node, source: &[u8]) -> Option<CallSite> {
+    let function = node.child_by_field_name("function")?;
+    let member = function.kind() == "member_expression";
+    let callee = match function.kind() {
+        "member_expression" => function.child_by_field_name("property")?.utf8_text(source).ok()?,
+        "identifier" => function.utf8_text(source).ok()?,
+        _ => return None,
+    };
+
+    let arguments: Vec<Node> = match node.child_by_field_name("arguments") {
+        Some(args) => {
+            let mut cursor = args.walk();
+            let arguments = args.named_children(&mut cursor)
+                .filter(|child| child.kind() != "comment")
+                .take(2)
+                .collect();
+            arguments
+        }
+        None => Vec::new(),
+    };
+    let argument = arguments.first().and_then(|first| call_argument(*first, source));
+    let options = match arguments.iter().find(|arg| arg.kind() == "object") {
+        Some(object) => Some(request_options(*object, source)),
+        // `fetch(url, init)`: the method is set somewhere we don't follow
+        None if arguments.len() > 1 => Some(RequestOptions { method_is_dynamic: true, ..RequestOptions::default() }),
+        None => None,
+    };
+
+    let position = node.start_position();
+    Some(CallSite {
+        callee: callee.to_string(),
+        member,
+        argument,
+        options,
+        line: position.row + 1,
+        column: position.column + 1,
+        snippet: snippet(node.utf8_text(source).ok()?),
+    })
+}
+
+fn call_argument(node: Node, source: &[u8]) -> Option<CallArgument> {
+    let text = node.utf8_text(source).ok()?;
+
+    match node.kind() {
+        "string" => Some(CallArgument::Literal(strip_delimiters(text).to_string())),
+        "template_string" => {
+            let template = UrlTemplate::new(template_parts(node, source));
+            if template.is_dynamic() {
+                Some(CallArgument::Template(template))
+            } else {
+                Some(CallArgument::Literal(strip_delimiters(text).to_string()))
+            }
+        }
+        "binary_expression" => {
+            let mut parts = Vec::new();
+            if !concatenation_parts(node, source, &mut parts) {
+                return None;
+            }
+            Some(CallArgument::Template(UrlTemplate::new(parts)))
+        }
+        "identifier" => Some(CallArgument::Identifier(text.to_string())),
+        "member_expression" => reference_name(node, source).map(CallArgument::Identifier),
+        _ => None,
+    }
+}
+
+/// Read `method` and `url` from an object literal
+fn request_options(object: Node, source: &[u8]) -> RequestOptions {
+    let mut options = RequestOptions::default();
+
+    let mut cursor = object.walk();
+    for property in object.named_children(&mut cursor) {
+        match property.kind() {
+            "pair" => {
+                let key = property.child_by_field_name("key")
+                    .and_then(|key| key.utf8_text(source).ok())
+                    .map(strip_quotes);
+                let value = match property.child_by_field_name("value") {
+                    Some(value) => value,
+                    None => continue,
+                };
+                match key {
+                    Some("method") => match call_argument(value, source) {
+                        Some(CallArgument::Literal(method)) => options.method = Some(method),
+                        _ => options.method_is_dynamic = true,
+                    },
+                    Some("url") => options.url = call_argument(value, source),
+                    _ => {}
+                }
+            }
+            // `{ method }` and `{ ...config }` hide the method
+            "shorthand_property_identifier" if property.utf8_text(source).ok() == Some("method") => {
+                options.method_is_dynamic = true;
+            }
+            "spread_element" => options.method_is_dynamic = true,
+            _ => {}
+        }
+    }
+
+    options
+}
+
+/// Strip quotes from a quoted object key (`'method'`)
+fn strip_quotes(key: &str) -> &str {
+    if key.starts_with(['"', '\'']) {
+        strip_delimiters(key)
+    } else {
+        key
+    }
+}
+
+/// `NAME` or `namespace.NAME` as a reference to a possible string constant
+fn reference_name(node: Node, source: &[u8]) -> Option<String> {
+    match node.kind() {
+        "identifier" => Some(node.utf8_text(source).ok()?.to_string()),
+        "member_expression" => {
+            let object = node.child_by_field_name("object")?;
+            let property = node.child_by_field_name("property")?;
+            if object.kind() != "identifier" {
+                return None;
+            }
+            Some(format!("{}.{}", object.utf8_text(source).ok()?, property.utf8_text(source).ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Split a template literal into its text and `${...}` substitutions
+fn template_parts(node: Node, source: &[u8]) -> Vec<UrlPart> {
+    let mut parts = Vec::new();
+    // Skip the opening backtick
+    let mut text_start = node.start_byte() + 1;
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() != "template_substitution" {
+            continue;
+        }
+        parts.push(literal_between(source, text_start, child.start_byte()));
+        // `${API_BASE}` may name a constant; anything more complex is dynamic
+        let reference = child.named_child(0).and_then(|expr| reference_name(expr, source));
+        parts.push(reference.map(UrlPart::Reference).unwrap_or(UrlPart::Dynamic));
+        text_start = child.end_byte();
+    }
+
+    parts.push(literal_between(source, text_start, node.end_byte().saturating_sub(1)));
+    parts
+}
+
+fn literal_between(source: &[u8], start: usize, end: usize) -> UrlPart {
+    let text = source.get(start..end.max(start)).unwrap_or_default();
+    UrlPart::Literal(String::from_utf8_lossy(text).into_owned())
+}
+
+/// Flatten a `+` chain into template parts.
+///
+/// Strings and template literals contribute their text, identifiers become
+/// references to possible constants, and every other operand is dynamic.
+/// Returns `false` when the chain contains neither a string nor a reference
+/// (e.g. `count + 1`), which is not a URL.
+fn concatenation_parts(node: Node, source: &[u8], parts: &mut Vec<UrlPart>) -> bool {
+    match node.kind() {
+        "binary_expression" => {
+            let operator = node.child_by_field_name("operator")
+                .and_then(|op| op.utf8_text(source).ok());
+            if operator != Some("+") {
+                parts.push(UrlPart::Dynamic);
+                return false;
+            }
+
+            let left = node.child_by_field_name("left")
+                .map(|left| concatenation_parts(left, source, parts))
+                .unwrap_or(false);
+            let right = node.child_by_field_name("right")
+                .map(|right| concatenation_parts(right, source, parts))
+                .unwrap_or(false);
+            left || right
+        }
+        "parenthesized_expression" => {
+            let mut cursor = node.walk();
+            let inner = node.named_children(&mut cursor).find(|child| child.kind() != "comment");
+            match inner {
+                Some(inner) => concatenation_parts(inner, source, parts),
+                None => false,
+            }
+        }
+        "string" => {
+            let text = node.utf8_text(source).unwrap_or_default();
+            parts.push(UrlPart::Literal(strip_delimiters(text).to_string()));
+            true
+        }
+        "template_string" => {
+            parts.extend(template_parts(node, source));
+            true
+        }
+        "identifier" | "member_expression" => match reference_name(node, source) {
+            Some(name) => {
+                parts.push(UrlPart::Reference(name));
+                true
+            }
+            None => {
+                parts.push(UrlPart::Dynamic);
+                false
+            }
+        },
+        _ => {
+            parts.push(UrlPart::Dynamic);
+            false
+        }
+    }
+}
+
+/// Longest snippet kept for a match
+const MAX_SNIPPET_CHARS: usize = 120;
+
+/// First line of `text`, trimmed and capped at [`MAX_SNIPPET_CHARS`]
+pub fn snippet(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((index, _)) => format!("{}...", &line[..index]),
+        None => line.to_string(),
+    }
+}
+
+/// Strip the surrounding quotes or backticks from a string token
+fn strip_delimiters(text: &str) -> &str {
+    if text.len() >= 2 {
+        &text[1..text.len() - 1]
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(source: &str) -> Option<RequestOptions> {
+        parse_source(source, "ts").unwrap().calls.remove(0).options
+    }
+
+    #[test]
+    fn init_variable_hides_the_method() {
+        assert!(options("fetch('/users', init);").unwrap().method_is_dynamic);
+        assert!(options("fetch('/users', { method });").unwrap().method_is_dynamic);
+    }
+
+    #[test]
+    fn literal_options_give_the_method() {
+        let literal = options("fetch('/users', { method: 'DELETE' });").unwrap();
+        assert_eq!(literal.method.as_deref(), Some("DELETE"));
+        assert!(!literal.method_is_dynamic);
+        assert!(options("fetch('/users');").is_none());
+    }
+
+    #[test]
+    fn member_calls_are_told_apart() {
+        let calls = parse_source("usersApi.listUsers(); listUsers();", "ts").unwrap().calls;
+        assert!(calls[0].member);
+        assert!(!calls[1].member);
+    }
+}
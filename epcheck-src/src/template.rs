@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Placeholder for a dynamic part while matching (never appears in source text)
+const DYNAMIC: char = '\u{0}';
+
+/// A path segment that is entirely dynamic
+const DYNAMIC_SEGMENT: &str = "\u{0}";
+
+/// One piece of a URL built in code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UrlPart {
+    Literal(String),
+    /// An interpolation (`${id}`) or non-string operand (`+ id`)
+    Dynamic,
+    /// An identifier that may name a string constant (`USERS_URL`,
+    /// `routes.USERS_URL`); treated as dynamic until resolved
+    Reference(String),
+}
+
+/// A URL argument normalized into literal text and dynamic parts.
+///
+/// `` `${API_BASE}/users/${id}/orders` `` and `BASE + '/users/' + id + '/orders'`
+/// both become `{*}/users/{*}/orders`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlTemplate {
+    parts: Vec<UrlPart>,
+}
+
+impl UrlTemplate {
+    /// Build a template, merging adjacent literals and adjacent dynamic parts
+    pub fn new(parts: impl IntoIterator<Item = UrlPart>) -> Self {
+        let mut merged: Vec<UrlPart> = Vec::new();
+
+        for part in parts {
+            match part {
+                UrlPart::Literal(text) if text.is_empty() => {}
+                UrlPart::Literal(text) => {
+                    if let Some(UrlPart::Literal(last)) = merged.last_mut() {
+                        last.push_str(&text);
+                    } else {
+                        merged.push(UrlPart::Literal(text));
+                    }
+                }
+                UrlPart::Dynamic => {
+                    if merged.last() != Some(&UrlPart::Dynamic) {
+                        merged.push(UrlPart::Dynamic);
+                    }
+                }
+                reference => merged.push(reference),
+            }
+        }
+
+        Self { parts: merged }
+    }
+
+    /// A template consisting of a single identifier to be resolved later
+    pub fn reference(name: &str) -> Self {
+        Self::new([UrlPart::Reference(name.to_string())])
+    }
+
+    /// A template consisting of a single string literal
+    pub fn literal(text: &str) -> Self {
+        Self::new([UrlPart::Literal(text.to_string())])
+    }
+
+    pub fn parts(&self) -> &[UrlPart] {
+        &self.parts
+    }
+
+    /// The text of a template that is a single literal
+    pub fn as_literal(&self) -> Option<&str> {
+        match self.parts.as_slice() {
+            [UrlPart::Literal(text)] => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.parts.iter().any(|part| !matches!(part, UrlPart::Literal(_)))
+    }
+
+    pub fn has_references(&self) -> bool {
+        self.parts.iter().any(|part| matches!(part, UrlPart::Reference(_)))
+    }
+
+    /// Whether this reads as a request path with at least one literal
+    /// segment, unlike a fully dynamic URL or a relative file name
+    pub fn has_literal_path(&self) -> bool {
+        self.normalized_path().is_some_and(|path| {
+            segments(&path).iter().any(|segment| segment.chars().any(|c| c != DYNAMIC))
+        })
+    }
+
+    /// Host of an absolute URL (`https://api.example.com/users`), without
+    /// credentials or port. `None` when part of the host is dynamic.
+    pub fn host(&self) -> Option<&str> {
+        let text = match self.parts.first()? {
+            UrlPart::Literal(text) => text,
+            _ => return None,
+        };
+        let (_, after_scheme) = text.split_once("://")?;
+        let end = after_scheme.find(['/', '?', '#']);
+        if end.is_none() && self.parts.len() > 1 {
+            return None;
+        }
+
+        let authority = &after_scheme[..end.unwrap_or(after_scheme.len())];
+        let host = authority.rsplit('@').next()?.split(':').next()?;
+        Some(host).filter(|host| !host.is_empty())
+    }
+
+    /// Substitute references with the templates `lookup` returns for them.
+    ///
+    /// References `lookup` cannot resolve become dynamic parts.
+    pub fn resolve(&self, mut lookup: impl FnMut(&str) -> Option<UrlTemplate>) -> UrlTemplate {
+        let mut parts = Vec::new();
+        for part in &self.parts {
+            match part {
+                UrlPart::Reference(name) => match lookup(name) {
+                    Some(template) => parts.extend(template.parts),
+                    None => parts.push(UrlPart::Dynamic),
+                },
+                part => parts.push(part.clone()),
+            }
+        }
+        UrlTemplate::new(parts)
+    }
+
+    /// Whether this call-site URL can address the given spec path.
+    ///
+    /// Segments are compared pairwise: `{param}` segments in the spec accept
+    /// any value, and dynamic segments in the call accept any spec segment. A
+    /// leading dynamic part is treated as a base URL; literal prefixes only
+    /// match spec paths that include them (server and basePath variants).
+    pub fn matches(&self, spec_path: &str) -> bool {
+        self.specificity(spec_path).is_some()
+    }
+
+    /// How closely this call-site URL fits a spec path it [`matches`](Self::matches)
+    pub fn specificity(&self, spec_path: &str) -> Option<Specificity> {
+        let call_path = self.normalized_path()?;
+        let call_segments = segments(&call_path);
+        let spec_segments = segments(spec_path);
+        if call_segments.len() != spec_segments.len() {
+            return None;
+        }
+
+        let mut fits = Vec::with_capacity(call_segments.len());
+        for (call, spec) in call_segments.iter().zip(&spec_segments) {
+            if !segment_matches(call, spec) {
+                return None;
+            }
+            fits.push(segment_fit(call, spec));
+        }
+        Some(Specificity(fits))
+    }
+
+    /// Render the path part with dynamic parts encoded as [`DYNAMIC`].
+    ///
+    /// Returns `None` when the template does not look like a path: plain
+    /// literals must start with `/` or be absolute URLs.
+    fn normalized_path(&self) -> Option<String> {
+        let mut parts = self.parts.as_slice();
+        let has_base = matches!(parts.first(), Some(UrlPart::Dynamic | UrlPart::Reference(_)));
+        if has_base {
+            parts = &parts[1..];
+        }
+
+        let mut text = String::new();
+        for part in parts {
+            match part {
+                UrlPart::Literal(literal) => text.push_str(literal),
+                UrlPart::Dynamic | UrlPart::Reference(_) => text.push(DYNAMIC),
+            }
+        }
+
+        // Drop the query string and fragment
+        if let Some(index) = text.find(['?', '#']) {
+            text.truncate(index);
+        }
+
+        // Drop scheme and host of absolute URLs
+        if let Some(index) = text.find("://") {
+            let after_scheme = &text[index + 3..];
+            text = match after_scheme.find('/') {
+                Some(slash) => after_scheme[slash..].to_string(),
+                None => "/".to_string(),
+            };
+        }
+
+        if text.starts_with('/') {
+            Some(text)
+        } else if has_base && !text.is_empty() {
+            Some(format!("/{}", text))
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for UrlTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            match part {
+                UrlPart::Literal(text) => write!(f, "{}", text)?,
+                UrlPart::Dynamic => write!(f, "{{*}}")?,
+                UrlPart::Reference(name) => write!(f, "${{{}}}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How closely a call URL fits a spec path: one score per segment, from the
+/// left. Literal call segments fit literal spec segments best, dynamic
+/// ones fit `{param}` segments best.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Specificity(Vec<u8>);
+
+impl Specificity {
+    /// At least as close a fit in every segment and closer in one, so
+    /// `/users/me` beats `/users/{id}` for a call to `/users/me`
+    pub fn beats(&self, other: &Specificity) -> bool {
+        self.0.len() == other.0.len()
+            && self != other
+            && self.0.iter().zip(&other.0).all(|(ours, theirs)| ours >= theirs)
+    }
+}
+
+/// Two spec paths one request can match, with neither more specific
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmbiguousPaths<'p> {
+    pub first: &'p str,
+    pub second: &'p str,
+    /// A request path both match
+    pub example: String,
+    /// Same template apart from parameter names (`/users/{id}`, `/users/{name}`)
+    pub identical: bool,
+}
+
+/// Pairs of spec paths that are identical apart from parameter names, or
+/// cross each other (`/{entity}/me` and `/books/{id}` both match `/books/me`,
+/// each with a concrete segment the other templates)
+pub fn ambiguous_paths<'p>(paths: &[&'p str]) -> Vec<AmbiguousPaths<'p>> {
+    let mut by_length: HashMap<usize, Vec<(&'p str, Vec<&'p str>)>> = HashMap::new();
+    for &path in paths {
+        let path_segments = segments(path);
+        by_length.entry(path_segments.len()).or_default().push((path, path_segments));
+    }
+
+    let mut ambiguous = Vec::new();
+    for group in by_length.values() {
+        for (index, (first, first_segments)) in group.iter().enumerate() {
+            for (second, second_segments) in &group[index + 1..] {
+                let example = match shared_request(first_segments, second_segments) {
+                    Some(example) => example,
+                    None => continue,
+                };
+
+                let first_levels: Vec<u8> = first_segments.iter().map(|segment| concreteness(segment)).collect();
+                let second_levels: Vec<u8> = second_segments.iter().map(|segment| concreteness(segment)).collect();
+                let at_least = |ours: &[u8], theirs: &[u8]| ours.iter().zip(theirs).all(|(a, b)| a >= b);
+                if first_levels != second_levels && (at_least(&first_levels, &second_levels) || at_least(&second_levels, &first_levels)) {
+                    continue;
+                }
+
+                let identical = first_segments.iter().zip(second_segments)
+                    .all(|(a, b)| replace_params(a) == replace_params(b));
+                let (first, second) = if first <= second { (*first, *second) } else { (*second, *first) };
+                ambiguous.push(AmbiguousPaths { first, second, example, identical });
+            }
+        }
+    }
+
+    ambiguous.sort_by(|a, b| (a.first, a.second).cmp(&(b.first, b.second)));
+    ambiguous
+}
+
+/// A request path matching both spec paths, segment by segment
+fn shared_request(first: &[&str], second: &[&str]) -> Option<String> {
+    let mut example = String::new();
+
+    for (a, b) in first.iter().zip(second) {
+        let segment = match (a.contains('{'), b.contains('{')) {
+            (false, false) => (a == b).then_some(*a)?,
+            (true, false) => literal_fits(a, b).then_some(*b)?,
+            (false, true) => literal_fits(b, a).then_some(*a)?,
+            (true, true) if concreteness(a) == 0 => *b,
+            (true, true) if concreteness(b) == 0 => *a,
+            // Two partly literal segments; only the same shape is known to overlap
+            (true, true) => (replace_params(a) == replace_params(b)).then_some(*a)?,
+        };
+        example.push('/');
+        example.push_str(segment);
+    }
+
+    if example.is_empty() {
+        example.push('/');
+    }
+    Some(example)
+}
+
+/// Whether a templated spec segment accepts a literal one
+fn literal_fits(templated: &str, literal: &str) -> bool {
+    let pattern: Vec<char> = replace_params(templated).chars().collect();
+    let text: Vec<char> = literal.chars().collect();
+    wildcard_match(&pattern, &text)
+}
+
+/// 2 for a literal spec segment, 1 for a partly literal one (`{name}.json`),
+/// 0 for a lone `{param}`
+fn concreteness(spec: &str) -> u8 {
+    if !spec.contains('{') {
+        2
+    } else if replace_params(spec) == DYNAMIC_SEGMENT {
+        0
+    } else {
+        1
+    }
+}
+
+/// Score of a spec segment for a call segment it matches, from 1 to 3
+fn segment_fit(call: &str, spec: &str) -> u8 {
+    let level = concreteness(spec);
+    if call == DYNAMIC_SEGMENT {
+        3 - level
+    } else if call.contains(DYNAMIC) {
+        // `user-${id}` fits `user-{id}` better than `{id}` or `user-admin`
+        [2, 3, 1][level as usize]
+    } else {
+        level + 1
+    }
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.trim_end_matches('/').split('/').skip(1).collect()
+}
+
+fn segment_matches(call: &str, spec: &str) -> bool {
+    let call_dynamic = call.contains(DYNAMIC);
+    let spec_templated = spec.contains('{');
+
+    if call == DYNAMIC_SEGMENT {
+        return true;
+    }
+
+    match (call_dynamic, spec_templated) {
+        (false, false) => call == spec,
+        // `{param}` accepts any value, including a partially dynamic one
+        (_, true) => {
+            let pattern: Vec<char> = replace_params(spec).chars().collect();
+            let text: Vec<char> = call.replace(DYNAMIC, "x").chars().collect();
+            wildcard_match(&pattern, &text)
+        }
+        (true, false) => {
+            let pattern: Vec<char> = call.chars().collect();
+            let text: Vec<char> = spec.chars().collect();
+            wildcard_match(&pattern, &text)
+        }
+    }
+}
+
+/// Replace every `{param}` in a spec segment with the wildcard marker
+fn replace_params(segment: &str) -> String {
+    let mut result = String::new();
+    let mut in_param = false;
+
+    for c in segment.chars() {
+        match c {
+            '{' => {
+                in_param = true;
+                result.push(DYNAMIC);
+            }
+            '}' if in_param => in_param = false,
+            _ if in_param => {}
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Glob-style match where [`DYNAMIC`] in `pattern` matches any run of characters
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == DYNAMIC {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == DYNAMIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(parts: &[Option<&str>]) -> UrlTemplate {
+        UrlTemplate::new(parts.iter().map(|part| match part {
+            Some(text) => UrlPart::Literal(text.to_string()),
+            None => UrlPart::Dynamic,
+        }))
+    }
+
+    #[test]
+    fn literal_urls_match_params() {
+        assert!(UrlTemplate::literal("/users/42").matches("/users/{id}"));
+        assert!(UrlTemplate::literal("/users/42?expand=1").matches("/users/{id}"));
+        assert!(UrlTemplate::literal("https://api.example.com/users").matches("/users"));
+        assert!(!UrlTemplate::literal("/users/42").matches("/orders/{id}"));
+        assert!(!UrlTemplate::literal("users/42").matches("/users/{id}"));
+    }
+
+    #[test]
+    fn segment_counts_must_agree() {
+        // `/orders/{*}` is not a base `/orders` plus a segment matching `users`
+        assert!(!template(&[Some("/orders/"), None]).matches("/users"));
+        assert!(!UrlTemplate::literal("/api/users").matches("/users"));
+        assert!(UrlTemplate::literal("/api/users").matches("/api/users"));
+        assert!(!UrlTemplate::literal("/users").matches("/users/{id}"));
+    }
+
+    #[test]
+    fn dynamic_parts_match_any_segment() {
+        assert!(template(&[Some("/users/"), None]).matches("/users/{id}"));
+        assert!(template(&[Some("/users/"), None]).matches("/users/me"));
+        assert!(template(&[None, Some("/users")]).matches("/users"));
+        assert!(template(&[Some("/files/"), None, Some(".json")]).matches("/files/{name}.json"));
+        assert!(!template(&[Some("/files/"), None, Some(".json")]).matches("/files/{name}.xml"));
+    }
+
+    #[test]
+    fn concrete_segments_beat_templated_ones() {
+        let call = UrlTemplate::literal("/users/me");
+        let concrete = call.specificity("/users/me").unwrap();
+        let templated = call.specificity("/users/{id}").unwrap();
+        assert!(concrete.beats(&templated));
+        assert!(!templated.beats(&concrete));
+    }
+
+    #[test]
+    fn dynamic_segments_prefer_templated_ones() {
+        let call = template(&[Some("/users/"), None]);
+        let templated = call.specificity("/users/{id}").unwrap();
+        let concrete = call.specificity("/users/me").unwrap();
+        assert!(templated.beats(&concrete));
+    }
+
+    #[test]
+    fn crossing_fits_beat_neither() {
+        let call = UrlTemplate::literal("/books/me");
+        let entity = call.specificity("/{entity}/me").unwrap();
+        let book = call.specificity("/books/{id}").unwrap();
+        assert!(!entity.beats(&book));
+        assert!(!book.beats(&entity));
+    }
+
+    #[test]
+    fn ambiguous_paths_are_flagged() {
+        let ambiguous = ambiguous_paths(&["/users/{id}", "/users/me", "/users/{name}", "/{entity}/me", "/books/{id}"]);
+        let pairs: Vec<(&str, &str, bool)> = ambiguous.iter().map(|a| (a.first, a.second, a.identical)).collect();
+        assert_eq!(pairs, vec![
+            ("/books/{id}", "/{entity}/me", false),
+            ("/users/{id}", "/users/{name}", true),
+            ("/users/{id}", "/{entity}/me", false),
+            ("/users/{name}", "/{entity}/me", false),
+        ]);
+    }
+}
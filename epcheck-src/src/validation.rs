@@ -0,0 +1,580 @@
+use crate::cli::OutputFormat;
+use crate::openapi::{strip_unresolved_refs, HttpMethod, SpecDialect};
+use crate::resolver::{escape_pointer_token, parse_document, percent_decode, resolve_refs, Revision};
+use crate::report::{print_csv_row, print_markdown_header};
+use crate::template::ambiguous_paths;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Path item keys that are not operations
+const PATH_ITEM_FIELDS: [&str; 5] = ["$ref", "summary", "description", "servers", "parameters"];
+
+/// `{name}` parameters in a path template
+static TEMPLATE_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}/]+)\}").unwrap());
+
+/// `{variable}` placeholders in a server URL
+static SERVER_VARIABLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}]*)\}").unwrap());
+
+/// Line number in a parser error message
+static ERROR_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"line (\d+)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// A single structural problem found in a specification
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    /// JSON pointer to the offending node (e.g. `/paths/~1users/get`)
+    pub pointer: String,
+    /// 1-based line in the source document, when it could be located
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Structural validator for a single OpenAPI document
+pub struct SpecValidator<'a> {
+    source: &'a str,
+    document: &'a Value,
+    root_file: PathBuf,
+    /// Git revision the document was read at; referenced files are read there too
+    revision: Option<Revision>,
+    findings: Vec<Finding>,
+}
+
+impl<'a> SpecValidator<'a> {
+    pub fn new(source: &'a str, document: &'a Value, root_file: &Path) -> Self {
+        Self {
+            source,
+            document,
+            root_file: root_file.to_path_buf(),
+            revision: None,
+            findings: Vec::new(),
+        }
+    }
+
+    /// Read relative file references at the git revision the document came from
+    pub fn at_revision(mut self, revision: Revision) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+
+    /// Run every check and return the findings ordered by line
+    pub fn validate(mut self) -> Vec<Finding> {
+        if let Err(e) = SpecDialect::detect(self.document) {
+            self.report(Severity::Error, String::new(), e.to_string());
+        }
+
+        self.check_refs(self.document, "");
+
+        // Path and server checks look through references; fall back to the raw
+        // document when resolution fails (those failures are reported above)
+        let resolved = match resolve_refs(self.document.clone(), &self.root_file, self.revision.as_ref()) {
+            Ok(mut resolved) => {
+                self.check_unresolved(&mut resolved);
+                resolved
+            }
+            Err(_) => self.document.clone(),
+        };
+        self.check_paths(&resolved);
+        self.check_servers(&resolved);
+
+        self.findings.sort_by_key(|f| f.line.unwrap_or(usize::MAX));
+        self.findings
+    }
+
+    fn report(&mut self, severity: Severity, pointer: String, message: String) {
+        let line = locate_line(self.source, self.document, &pointer);
+        self.findings.push(Finding {
+            severity,
+            pointer,
+            line,
+            message,
+        });
+    }
+
+    /// Report `$ref` values that do not point at an existing node
+    fn check_refs(&mut self, value: &Value, pointer: &str) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    if let Err(reason) = self.check_ref(reference) {
+                        self.report(Severity::Error, format!("{}/$ref", pointer),
+                                    format!("unresolved reference '{}': {}", reference, reason));
+                    }
+                }
+                for (key, child) in map {
+                    self.check_refs(child, &format!("{}/{}", pointer, escape_pointer_token(key)));
+                }
+            }
+            Value::Array(items) => {
+                for (index, child) in items.iter().enumerate() {
+                    self.check_refs(child, &format!("{}/{}", pointer, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_ref(&self, reference: &str) -> Result<(), String> {
+        if reference.starts_with("http://") || reference.starts_with("https://") {
+            return Ok(());
+        }
+
+        let (target, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let missing = || format!("no node at #{}", fragment);
+        // Decode like the resolver, so `validate` and `check` agree on every ref
+        let pointer = percent_decode(fragment);
+        let target = percent_decode(target);
+
+        if target.is_empty() {
+            return self.document.pointer(&pointer).map(|_| ()).ok_or_else(missing);
+        }
+
+        if self.root_file.as_os_str().is_empty() {
+            return Err("relative file references require a local spec file".to_string());
+        }
+        let base_dir = self.root_file.parent().unwrap_or_else(|| Path::new("."));
+        let path = base_dir.join(&target);
+        let content = match &self.revision {
+            Some(revision) => revision.read(&path),
+            None => std::fs::read_to_string(&path).map_err(|e| e.to_string()),
+        };
+        let content = content.map_err(|e| format!("{}: {}", path.display(), e))?;
+        let document = parse_document(&content, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        document.pointer(&pointer).map(|_| ()).ok_or_else(missing)
+    }
+
+    /// Report path items and parameters left as references after resolution,
+    /// which `check` skips
+    fn check_unresolved(&mut self, resolved: &mut Value) {
+        for (pointer, reference) in strip_unresolved_refs(resolved) {
+            let kind = if reference.starts_with("http://") || reference.starts_with("https://") {
+                "remote"
+            } else {
+                "cyclic"
+            };
+            self.report(Severity::Warning, pointer, format!("{} reference '{}' is not followed, so it is skipped", kind, reference));
+        }
+    }
+
+    fn check_paths(&mut self, document: &Value) {
+        let paths = match document.get("paths").and_then(Value::as_object) {
+            Some(paths) => paths,
+            None => return,
+        };
+
+        let mut operation_ids: HashMap<String, String> = HashMap::new();
+
+        for (path, path_item) in paths {
+            let path_pointer = format!("/paths/{}", escape_pointer_token(path));
+
+            if !path.starts_with('/') {
+                self.report(Severity::Error, path_pointer.clone(), format!("path '{}' must start with '/'", path));
+            }
+
+            let path_item = match path_item.as_object() {
+                Some(item) => item,
+                None => continue,
+            };
+
+            let path_params = path_parameter_names(path_item.get("parameters"));
+            let template_params: Vec<String> = TEMPLATE_PARAM.captures_iter(path)
+                .map(|c| c[1].to_string())
+                .collect();
+
+            for (key, operation) in path_item {
+                if PATH_ITEM_FIELDS.contains(&key.as_str()) || key.starts_with("x-") {
+                    continue;
+                }
+
+                let operation_pointer = format!("{}/{}", path_pointer, escape_pointer_token(key));
+                if HttpMethod::from_str(key).is_none() {
+                    self.report(Severity::Error, operation_pointer, format!("unknown HTTP method '{}' on path '{}'", key, path));
+                    continue;
+                }
+
+                let mut defined = path_params.clone();
+                defined.extend(path_parameter_names(operation.get("parameters")));
+                for name in &template_params {
+                    if !defined.contains(name) {
+                        self.report(Severity::Error, operation_pointer.clone(),
+                                    format!("path parameter '{{{}}}' has no matching 'in: path' parameter definition", name));
+                    }
+                }
+
+                if let Some(operation_id) = operation.get("operationId").and_then(Value::as_str) {
+                    match operation_ids.get(operation_id) {
+                        Some(first) => {
+                            let message = format!("duplicate operationId '{}' (first defined at #{})", operation_id, first);
+                            self.report(Severity::Error, format!("{}/operationId", operation_pointer), message);
+                        }
+                        None => {
+                            operation_ids.insert(operation_id.to_string(), operation_pointer.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let path_names: Vec<&str> = paths.keys().map(String::as_str).collect();
+        for ambiguity in ambiguous_paths(&path_names) {
+            let message = if ambiguity.identical {
+                format!("path '{}' is identical to '{}' apart from parameter names", ambiguity.second, ambiguity.first)
+            } else {
+                format!("paths '{}' and '{}' both match requests such as '{}', and neither is more specific",
+                        ambiguity.first, ambiguity.second, ambiguity.example)
+            };
+            self.report(Severity::Warning, format!("/paths/{}", escape_pointer_token(ambiguity.second)), message);
+        }
+    }
+
+    fn check_servers(&mut self, document: &Value) {
+        let mut servers = Vec::new();
+        collect_servers(document.get("servers"), "/servers", &mut servers);
+
+        if let Some(paths) = document.get("paths").and_then(Value::as_object) {
+            for (path, path_item) in paths {
+                let path_pointer = format!("/paths/{}", escape_pointer_token(path));
+                collect_servers(path_item.get("servers"), &format!("{}/servers", path_pointer), &mut servers);
+
+                if let Some(path_item) = path_item.as_object() {
+                    for (key, operation) in path_item {
+                        if HttpMethod::from_str(key).is_some() {
+                            let pointer = format!("{}/{}/servers", path_pointer, escape_pointer_token(key));
+                            collect_servers(operation.get("servers"), &pointer, &mut servers);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (pointer, server) in servers {
+            if let Err(message) = check_server(server) {
+                self.report(Severity::Error, format!("{}/url", pointer), message);
+            }
+        }
+
+        // Swagger 2.0 host must be a bare host name, basePath an absolute path
+        if let Some(host) = document.get("host").and_then(Value::as_str) {
+            if host.contains("://") || host.contains('/') || host.chars().any(char::is_whitespace) {
+                self.report(Severity::Error, "/host".to_string(), format!("invalid host '{}': expected a host name without scheme or path", host));
+            }
+        }
+        if let Some(base_path) = document.get("basePath").and_then(Value::as_str) {
+            if !base_path.starts_with('/') {
+                self.report(Severity::Error, "/basePath".to_string(), format!("invalid basePath '{}': must start with '/'", base_path));
+            }
+        }
+    }
+}
+
+/// Names of `in: path` parameters in a parameter list
+fn path_parameter_names(parameters: Option<&Value>) -> HashSet<String> {
+    parameters
+        .and_then(Value::as_array)
+        .map(|params| {
+            params.iter()
+                .filter(|p| p.get("in").and_then(Value::as_str) == Some("path"))
+                .filter_map(|p| p.get("name").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn collect_servers<'v>(servers: Option<&'v Value>, pointer: &str, out: &mut Vec<(String, &'v Value)>) {
+    if let Some(servers) = servers.and_then(Value::as_array) {
+        for (index, server) in servers.iter().enumerate() {
+            out.push((format!("{}/{}", pointer, index), server));
+        }
+    }
+}
+
+/// Check a server object: variables must be defined and the expanded URL well-formed
+fn check_server(server: &Value) -> Result<(), String> {
+    let url = server.get("url").and_then(Value::as_str)
+        .ok_or_else(|| "server is missing a 'url' string".to_string())?;
+
+    let variables = server.get("variables").and_then(Value::as_object);
+    let mut expanded = url.to_string();
+
+    for capture in SERVER_VARIABLE.captures_iter(url) {
+        let name = &capture[1];
+        let default = variables
+            .and_then(|vars| vars.get(name))
+            .and_then(|var| var.get("default"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("server URL '{}' uses variable '{{{}}}' without a default value", url, name))?;
+        expanded = expanded.replace(&capture[0], default);
+    }
+
+    if expanded.is_empty() || expanded.chars().any(char::is_whitespace) {
+        return Err(format!("invalid server URL '{}'", url));
+    }
+
+    if let Some((scheme, rest)) = expanded.split_once("://") {
+        let valid_scheme = scheme.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c));
+        let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+        if !valid_scheme || host.is_empty() {
+            return Err(format!("invalid server URL '{}'", url));
+        }
+    }
+
+    Ok(())
+}
+
+/// Best-effort mapping from a JSON pointer to a line in the source text.
+///
+/// Walks the pointer one key at a time, searching forward from the previous
+/// match for the key in either JSON (`"key":`) or YAML (`key:`) form. Array
+/// indices skip ahead to the n-th occurrence of the following key, so the
+/// result points at the nearest locatable ancestor when the exact node cannot
+/// be found, and is `None` when not even the first key can.
+pub fn locate_line(source: &str, document: &Value, pointer: &str) -> Option<usize> {
+    let tokens: Vec<String> = pointer.split('/').skip(1)
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect();
+
+    let mut position = 0;
+    let mut located = None;
+    let mut skip = 0;
+    let mut node = Some(document);
+
+    for token in &tokens {
+        if let Some(Value::Array(items)) = node {
+            let index: usize = token.parse().ok()?;
+            node = items.get(index);
+            skip = index;
+            continue;
+        }
+        node = node.and_then(|n| n.get(token));
+
+        let mut found = None;
+        let mut from = position;
+        for _ in 0..=skip {
+            found = find_key(source, token, from);
+            match found {
+                Some(index) => from = index + token.len(),
+                None => break,
+            }
+        }
+        skip = 0;
+
+        match found {
+            Some(index) => {
+                position = index;
+                located = Some(index);
+            }
+            None => break,
+        }
+    }
+
+    located.map(|offset| source[..offset].matches('\n').count() + 1)
+}
+
+/// Find the next occurrence of `key` used as a mapping key at or after `from`
+fn find_key(source: &str, key: &str, from: usize) -> Option<usize> {
+    let mut search_from = from;
+
+    while let Some(relative) = source.get(search_from..)?.find(key) {
+        let start = search_from + relative;
+        let end = start + key.len();
+        let before = source[..start].chars().next_back();
+        let after = source[end..].chars().next();
+
+        let quoted = matches!((before, after), (Some('"'), Some('"')) | (Some('\''), Some('\'')));
+        let key_end = if quoted { end + 1 } else { end };
+        let followed_by_colon = source[key_end..].trim_start_matches([' ', '\t']).starts_with(':');
+        let bare_yaml_key = !quoted && before.is_none_or(|c| c == ' ' || c == '\t' || c == '\n' || c == '-');
+
+        if followed_by_colon && (quoted || bare_yaml_key) {
+            return Some(if quoted { start - 1 } else { start });
+        }
+        search_from = end;
+    }
+
+    None
+}
+
+/// Parse and validate a specification, returning every finding
+pub fn validate_spec(source: &str, spec_path: &str) -> Vec<Finding> {
+    let path = Path::new(spec_path);
+
+    let document = match parse_document(source, path) {
+        Ok(document) => document,
+        Err(e) => {
+            let message = e.to_string();
+            let line = ERROR_LINE.captures(&message)
+                .and_then(|c| c[1].parse().ok());
+            return vec![Finding {
+                severity: Severity::Error,
+                pointer: String::new(),
+                line,
+                message: format!("failed to parse document: {}", message),
+            }];
+        }
+    };
+
+    let (root_file, revision) = crate::cli::spec_root(spec_path);
+    let mut validator = SpecValidator::new(source, &document, root_file);
+    if let Some(revision) = revision {
+        validator = validator.at_revision(revision);
+    }
+    validator.validate()
+}
+
+/// Print validation findings in the requested format
+pub fn print_findings(findings: &[Finding], spec_path: &str, format: OutputFormat) -> anyhow::Result<()> {
+    let location = |f: &Finding| match f.line {
+        Some(line) => format!("{}:{}", spec_path, line),
+        None => spec_path.to_string(),
+    };
+
+    match format {
+        OutputFormat::Table => {
+            for finding in findings {
+                println!("{:7} {} #{}: {}", finding.severity.as_str(), location(finding), finding.pointer, finding.message);
+            }
+            println!("\n{} finding(s) in {}", findings.len(), spec_path);
+        }
+        OutputFormat::Csv => {
+            println!("Severity,File,Line,Pointer,Message");
+            for finding in findings {
+                print_csv_row(&[
+                    finding.severity.as_str(),
+                    spec_path,
+                    &finding.line.map(|l| l.to_string()).unwrap_or_default(),
+                    &finding.pointer,
+                    &finding.message,
+                ]);
+            }
+        }
+        OutputFormat::Json => {
+            let items: Vec<Value> = findings.iter()
+                .map(|f| serde_json::json!({
+                    "severity": f.severity.as_str(),
+                    "file": spec_path,
+                    "line": f.line,
+                    "pointer": f.pointer,
+                    "message": f.message
+                }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "findings": items }))?);
+        }
+        OutputFormat::Markdown => {
+            print_markdown_header(&["Severity", "Location", "Pointer", "Message"]);
+            for finding in findings {
+                println!("| {} | {} | `#{}` | {} |", finding.severity.as_str(), location(finding), finding.pointer, finding.message);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(source: &str) -> Vec<Finding> {
+        let document = parse_document(source, Path::new("openapi.yaml")).unwrap();
+        SpecValidator::new(source, &document, Path::new("")).validate()
+    }
+
+    fn messages(findings: &[Finding]) -> Vec<(Option<usize>, &str)> {
+        findings.iter().map(|f| (f.line, f.message.as_str())).collect()
+    }
+
+    #[test]
+    fn duplicate_operation_ids_are_errors() {
+        let findings = validate(concat!(
+            "openapi: 3.0.3\n",
+            "paths:\n",
+            "  /users:\n",
+            "    get:\n",
+            "      operationId: listUsers\n",
+            "  /people:\n",
+            "    get:\n",
+            "      operationId: listUsers\n",
+        ));
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[0].pointer.ends_with("/get/operationId"));
+        assert!(findings[0].message.starts_with("duplicate operationId 'listUsers'"));
+    }
+
+    #[test]
+    fn path_parameters_need_a_definition() {
+        let findings = validate(concat!(
+            "openapi: 3.0.3\n",
+            "paths:\n",
+            "  /users/{id}/posts/{postId}:\n",
+            "    parameters:\n",
+            "      - {name: id, in: path, required: true}\n",
+            "    get:\n",
+            "      parameters:\n",
+            "        - {name: postId, in: query}\n",
+        ));
+
+        assert_eq!(messages(&findings), [
+            (Some(6), "path parameter '{postId}' has no matching 'in: path' parameter definition"),
+        ]);
+    }
+
+    #[test]
+    fn server_urls_are_checked() {
+        let findings = validate(concat!(
+            "openapi: 3.0.3\n",
+            "servers:\n",
+            "  - url: https://{region}.example.com\n",
+            "  - url: https://\n",
+            "  - url: 'ht tp://example.com'\n",
+            "  - url: /api/v2\n",
+            "  - url: https://{env}.example.com\n",
+            "    variables:\n",
+            "      env: {default: api}\n",
+            "paths: {}\n",
+        ));
+
+        assert_eq!(messages(&findings), [
+            (Some(3), "server URL 'https://{region}.example.com' uses variable '{region}' without a default value"),
+            (Some(4), "invalid server URL 'https://'"),
+            (Some(5), "invalid server URL 'ht tp://example.com'"),
+        ]);
+    }
+
+    #[test]
+    fn lines_are_located_in_json_and_yaml() {
+        let yaml = "openapi: 3.0.3\npaths:\n  /users:\n    get:\n      parameters:\n        - name: a\n        - name: b\n";
+        let document = parse_document(yaml, Path::new("openapi.yaml")).unwrap();
+        assert_eq!(locate_line(yaml, &document, "/paths/~1users/get"), Some(4));
+        assert_eq!(locate_line(yaml, &document, "/paths/~1users/get/parameters/1/name"), Some(7));
+        // Unknown children fall back to the nearest ancestor
+        assert_eq!(locate_line(yaml, &document, "/paths/~1users/post"), Some(3));
+        assert_eq!(locate_line(yaml, &document, "/servers"), None);
+        assert_eq!(locate_line(yaml, &document, ""), None);
+
+        let json = "{\n  \"openapi\": \"3.0.3\",\n  \"paths\": {\n    \"/users\": {\n      \"get\": {}\n    }\n  }\n}\n";
+        let document = parse_document(json, Path::new("openapi.json")).unwrap();
+        assert_eq!(locate_line(json, &document, "/paths/~1users/get"), Some(5));
+        assert_eq!(locate_line(json, &document, "/servers/0/url"), None);
+    }
+}